@@ -0,0 +1,106 @@
+use std::io;
+use std::path::{ PathBuf };
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::{ fs, task };
+
+use crate::{ FileFolder, IndexWarning, ItemType, Tree, VaultIndex, VaultIndexBuilder };
+use crate::content_cache::ContentCache;
+
+impl VaultIndex {
+    /// Asynchronous version of [`VaultIndex::new`]. Walks the vault and
+    /// reads notes with `tokio::fs`, and parses each note on tokio's
+    /// blocking thread pool so that regex and YAML work doesn't hold up
+    /// the async workers.
+    pub async fn new_async(path_to_vault: Option<&str>, include_obsidian_folder: bool) -> Result<Self, io::Error> {
+        VaultIndexBuilder::new(path_to_vault)
+            .include_obsidian_folder(include_obsidian_folder)
+            .build_async()
+            .await
+    }
+
+    async fn from_builder_async(options: &VaultIndexBuilder) -> Result<Self, io::Error> {
+        // A custom source has no async interface, so it's read as usual.
+        if options.source.is_some() {
+            return Self::from_builder(options);
+        }
+        let mut name: String = Default::default();
+        let mut path: PathBuf = PathBuf::new();
+        let mut file_collection: Vec<FileFolder> = vec![];
+        let mut tree: Tree = Tree::new();
+        let mut warnings: Vec<IndexWarning> = vec![];
+        if let Some(vault_path) = &options.path_to_vault {
+            let p = vault_path.clone();
+            name = p.file_name().unwrap().to_str().unwrap().to_owned();
+            tree.arena[0].name = name.clone();
+            path = p.clone();
+            if !fs::metadata(&p).await.map(|m| m.is_dir()).unwrap_or(false) {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "The path specified either could not be found, could not be accessed, or was not a directory."));
+            }
+
+            Self::recursive_generate_filefolders_async(&p, &p, options, &mut tree, 0, &mut file_collection, &mut warnings).await?;
+        }
+
+        Ok(Self::from_filefolders(name, path, file_collection, tree, warnings))
+    }
+
+    /// Asynchronous version of `VaultIndex::recursive_generate_filefolders`,
+    /// walking the vault in the same order.
+    async fn recursive_generate_filefolders_async(dir_path: &PathBuf, vault_path: &PathBuf, options: &VaultIndexBuilder, tree: &mut Tree, tree_parent: usize, file_collection: &mut Vec<FileFolder>, warnings: &mut Vec<IndexWarning>) -> Result<(), io::Error> {
+        if options.max_depth.is_some_and(|max_depth| tree.arena[tree_parent].depth >= max_depth) {
+            return Ok(());
+        }
+        let mut entries = fs::read_dir(dir_path).await?;
+        let mut child_files: Vec<fs::DirEntry> = vec![];
+        while let Some(child_file) = entries.next_entry().await? {
+            child_files.push(child_file);
+        }
+        child_files.sort_by_key(|child_file| child_file.file_name());
+        for child_file in child_files {
+            let child_path = child_file.path();
+            let child_name = child_path.file_name().unwrap().to_str().unwrap().to_owned();
+            if child_file.file_type().await?.is_dir() {
+                if !options.include_obsidian_folder && child_name == ".obsidian" {
+                    continue;
+                }
+                file_collection.push(Self::generate_folder_item(&child_path, vault_path)?);
+                let idx = tree.add_child(tree_parent, child_name, ItemType::Folder).expect("Couldn't find parent in tree!");
+                Box::pin(Self::recursive_generate_filefolders_async(&child_path, vault_path, options, tree, idx, file_collection, warnings)).await?;
+            } else if options.is_note(&child_path) {
+                tree.add_child(tree_parent, child_path.file_stem().unwrap().to_str().unwrap().to_owned(), ItemType::Note);
+                let mut local_path = child_path.strip_prefix(vault_path).unwrap().to_path_buf();
+                local_path.set_extension("");
+                let modified: Option<SystemTime> = fs::metadata(&child_path).await?.modified().ok();
+                let contents = fs::read_to_string(&child_path).await?;
+                let note_path = child_path.clone();
+                let parse_options = options.parse_options.clone();
+                let (note, note_warnings) = task::spawn_blocking(move || {
+                    Self::parse_note_item(&note_path, local_path, &contents, modified, &parse_options)
+                }).await.map_err(io::Error::other)?;
+                options.handle_warnings(note_warnings, warnings)?;
+                file_collection.push(FileFolder::Note(Box::new(note)));
+                options.report_progress(&child_path);
+            } else if !options.notes_only {
+                tree.add_child(tree_parent, child_name, ItemType::File);
+                file_collection.push(Self::generate_file_item(&child_path, vault_path, None)?);
+                options.report_progress(&child_path);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl VaultIndexBuilder {
+    /// Asynchronous version of [`VaultIndexBuilder::build`]. See
+    /// [`VaultIndex::new_async`].
+    /// A vault with a custom [`VaultSource`](crate::VaultSource) is
+    /// indexed synchronously.
+    pub async fn build_async(self) -> Result<VaultIndex, io::Error> {
+        let mut index = VaultIndex::from_builder_async(&self).await?;
+        index.content_cache = Mutex::new(ContentCache::new(self.content_cache));
+        if self.resolve_links_eagerly {
+            index.resolve_all_links();
+        }
+        Ok(index)
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ self, BufReader, BufWriter, Write };
+use std::ops::Range;
+use std::path::{ Path, PathBuf };
+use std::time::SystemTime;
+use bincode::Options;
+use chrono::{ DateTime, Utc };
+use indexmap::IndexMap;
+use serde::{ Deserialize, Serialize };
+
+use crate::{ FileItem, FolderItem, IndexWarning, NoteItem, Property, Tree, VaultIndex };
+
+/// Bump whenever the layout of [`Cache`] changes, so that caches written
+/// by an older layout are rejected instead of misread.
+const CACHE_FORMAT: u32 = 9;
+
+/// Written before the cache itself, and checked before reading the rest.
+#[derive(Serialize, Deserialize, PartialEq)]
+struct CacheHeader {
+    format: u32,
+    crate_version: String,
+}
+impl CacheHeader {
+    fn current() -> Self {
+        Self {
+            format: CACHE_FORMAT,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// The cached form of a [`VaultIndex`]. bincode can't handle untagged
+/// enums or skipped fields, so notes and their properties are stored in
+/// plain mirrors of their usual types.
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    name: String,
+    path: Option<PathBuf>,
+    notes: Vec<(String, CachedNote)>,
+    files: Vec<(String, CachedFile)>,
+    folders: Vec<CachedFolder>,
+    filepath_ref: Vec<(String, String)>,
+    tags: Vec<(String, Vec<String>)>,
+    properties: Vec<String>,
+    tree: Tree,
+    warnings: Vec<IndexWarning>,
+    resolve_links_eagerly: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedProperty {
+    Text(String),
+    Number(f64),
+    Checkbox(bool),
+    List(Vec<CachedProperty>),
+    Map(IndexMap<String, CachedProperty>),
+    Date(DateTime<Utc>),
+    Unknown,
+}
+impl From<&Property> for CachedProperty {
+    fn from(property: &Property) -> Self {
+        match property {
+            Property::Text(t) => Self::Text(t.clone()),
+            Property::Number(n) => Self::Number(*n),
+            Property::Checkbox(b) => Self::Checkbox(*b),
+            Property::List(l) => Self::List(l.iter().map(Self::from).collect()),
+            Property::Map(m) => Self::Map(m.iter().map(|(k, v)| (k.clone(), Self::from(v))).collect()),
+            Property::Date(d) => Self::Date(*d),
+            Property::Unknown => Self::Unknown,
+        }
+    }
+}
+impl From<CachedProperty> for Property {
+    fn from(property: CachedProperty) -> Self {
+        match property {
+            CachedProperty::Text(t) => Self::Text(t),
+            CachedProperty::Number(n) => Self::Number(n),
+            CachedProperty::Checkbox(b) => Self::Checkbox(b),
+            CachedProperty::List(l) => Self::List(l.into_iter().map(Self::from).collect()),
+            CachedProperty::Map(m) => Self::Map(m.into_iter().map(|(k, v)| (k, Self::from(v))).collect()),
+            CachedProperty::Date(d) => Self::Date(d),
+            CachedProperty::Unknown => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedNote {
+    name: String,
+    title: String,
+    file_type: String,
+    path: PathBuf,
+    local_path: PathBuf,
+    properties: Vec<(String, CachedProperty)>,
+    tags: Vec<String>,
+    raw_tags: Vec<String>,
+    tag_occurrences: Vec<(String, usize, usize)>,
+    frontmatter_span: Option<Range<usize>>,
+    modified: Option<SystemTime>,
+    embeds: Vec<String>,
+    links: Vec<String>,
+    resolved_links: Vec<Option<String>>,
+    external_links: Vec<String>,
+    blocks: HashMap<String, usize>,
+    cssclasses: Vec<String>,
+    raw_key: HashMap<String, String>,
+    archive: Option<PathBuf>,
+}
+impl From<&NoteItem> for CachedNote {
+    fn from(note: &NoteItem) -> Self {
+        Self {
+            name: note.name.clone(),
+            title: note.title.clone(),
+            file_type: note.file_type.clone(),
+            path: note.path.clone(),
+            local_path: note.local_path.clone(),
+            properties: note.properties.iter().map(|(k, v)| (k.clone(), CachedProperty::from(v))).collect(),
+            tags: note.tags.clone(),
+            raw_tags: note.raw_tags.clone(),
+            tag_occurrences: note.tag_occurrences.clone(),
+            frontmatter_span: note.frontmatter_span.clone(),
+            modified: note.modified,
+            embeds: note.embeds.clone(),
+            links: note.links.clone(),
+            resolved_links: note.resolved_links.clone(),
+            external_links: note.external_links.clone(),
+            blocks: note.blocks.clone(),
+            cssclasses: note.cssclasses.clone(),
+            raw_key: note.raw_key.clone(),
+            archive: note.archive.clone(),
+        }
+    }
+}
+impl From<CachedNote> for NoteItem {
+    fn from(note: CachedNote) -> Self {
+        Self {
+            name: note.name,
+            title: note.title,
+            file_type: note.file_type,
+            path: note.path,
+            local_path: note.local_path,
+            properties: note.properties.into_iter().map(|(k, v)| (k, Property::from(v))).collect(),
+            tags: note.tags,
+            raw_tags: note.raw_tags,
+            tag_occurrences: note.tag_occurrences,
+            frontmatter_span: note.frontmatter_span,
+            modified: note.modified,
+            embeds: note.embeds,
+            links: note.links,
+            resolved_links: note.resolved_links,
+            external_links: note.external_links,
+            blocks: note.blocks,
+            cssclasses: note.cssclasses,
+            raw_key: note.raw_key,
+            archive: note.archive,
+            source: None,
+            parse_options: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    name: String,
+    file_type: String,
+    path: PathBuf,
+    local_path: PathBuf,
+    archive: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFolder {
+    name: String,
+    path: PathBuf,
+    local_path: PathBuf,
+}
+
+fn to_io_error(e: bincode::ErrorKind) -> io::Error {
+    match e {
+        bincode::ErrorKind::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+impl VaultIndex {
+    /// Saves the index to a compact binary cache file, which
+    /// [`VaultIndex::load_cache`] can read back far faster than the vault
+    /// can be indexed again. Only the index is saved, not the contents of
+    /// notes, which are still read from the vault as needed.
+    pub fn save_cache(&self, path: &Path) -> Result<(), io::Error> {
+        let cache = Cache {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            notes: self.notes.iter().map(|(k, note)| (k.clone(), CachedNote::from(note))).collect(),
+            files: self.files.iter().map(|(k, file)| (k.clone(), CachedFile {
+                name: file.name.clone(),
+                file_type: file.file_type.clone(),
+                path: file.path.clone(),
+                local_path: file.local_path.clone(),
+                archive: file.archive.clone(),
+            })).collect(),
+            folders: self.folders.iter().map(|folder| CachedFolder {
+                name: folder.name.clone(),
+                path: folder.path.clone(),
+                local_path: folder.local_path.clone(),
+            }).collect(),
+            filepath_ref: self.filepath_ref.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            tags: self.tags.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            properties: self.properties.clone(),
+            tree: self.tree.clone(),
+            warnings: self.warnings.clone(),
+            resolve_links_eagerly: self.resolve_links_eagerly,
+        };
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::options().serialize_into(&mut writer, &CacheHeader::current()).map_err(|e| to_io_error(*e))?;
+        bincode::options().serialize_into(&mut writer, &cache).map_err(|e| to_io_error(*e))?;
+        writer.flush()
+    }
+
+    /// Loads an index saved with [`VaultIndex::save_cache`]. A cache saved
+    /// by a different version of this crate is rejected with `InvalidData`.
+    pub fn load_cache(path: &Path) -> Result<Self, io::Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+        // The limit stops a file that isn't a cache from being taken for
+        // a header with a huge string in it.
+        let header: CacheHeader = bincode::options().with_limit(1024).deserialize_from(&mut reader)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not a vault index cache!"))?;
+        if header != CacheHeader::current() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                "The cache was saved by version {} of the cache format (vault_dweller {}); it must be rebuilt!",
+                header.format, header.crate_version,
+            )));
+        }
+        let cache: Cache = bincode::options().deserialize_from(&mut reader).map_err(|e| to_io_error(*e))?;
+        Ok(Self {
+            name: cache.name,
+            path: cache.path,
+            notes: cache.notes.into_iter().map(|(k, note)| (k, NoteItem::from(note))).collect(),
+            files: cache.files.into_iter().map(|(k, file)| (k, FileItem {
+                name: file.name,
+                file_type: file.file_type,
+                path: file.path,
+                local_path: file.local_path,
+                archive: file.archive,
+                source: None,
+            })).collect(),
+            folders: cache.folders.into_iter().map(|folder| FolderItem {
+                name: folder.name,
+                path: folder.path,
+                local_path: folder.local_path,
+            }).collect(),
+            filepath_ref: cache.filepath_ref.into_iter().collect::<IndexMap<String, String>>(),
+            tags: cache.tags.into_iter().collect::<IndexMap<String, Vec<String>>>(),
+            properties: cache.properties,
+            tree: cache.tree,
+            warnings: cache.warnings,
+            virtual_contents: HashMap::new(),
+            content_cache: Default::default(),
+            resolve_links_eagerly: cache.resolve_links_eagerly,
+        })
+    }
+}
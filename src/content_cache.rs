@@ -0,0 +1,105 @@
+use std::path::{ Path, PathBuf };
+use indexmap::IndexMap;
+
+/// How much a [`VaultIndex`](crate::VaultIndex) may hold in its cache of
+/// note contents; see [`VaultIndexBuilder::content_cache`].
+///
+/// [`VaultIndexBuilder::content_cache`]: crate::VaultIndexBuilder::content_cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheCapacity {
+    /// At most this many notes.
+    Notes(usize),
+    /// At most this many bytes of note contents in all.
+    Bytes(usize),
+}
+
+/// How a [`VaultIndex`](crate::VaultIndex)'s cache of note contents has
+/// been used. Returned by
+/// [`VaultIndex::cache_stats`](crate::VaultIndex::cache_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub notes: usize,
+    pub bytes: usize,
+}
+
+/// A least-recently-used cache of note contents, keyed by local path.
+/// Entries are kept from least to most recently read.
+#[derive(Debug, Default)]
+pub(crate) struct ContentCache {
+    capacity: Option<CacheCapacity>,
+    entries: IndexMap<PathBuf, String>,
+    stats: CacheStats,
+}
+
+impl ContentCache {
+    pub(crate) fn new(capacity: Option<CacheCapacity>) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    /// Looks up a note's contents, marking them as the most recently
+    /// read. Always misses if the cache is off.
+    pub(crate) fn get(&mut self, local_path: &Path) -> Option<String> {
+        self.capacity?;
+        match self.entries.shift_remove(local_path) {
+            Some(contents) => {
+                self.stats.hits += 1;
+                self.entries.insert(local_path.to_path_buf(), contents.clone());
+                Some(contents)
+            },
+            None => {
+                self.stats.misses += 1;
+                None
+            },
+        }
+    }
+
+    /// Adds a note's contents, evicting the least recently read notes
+    /// until the cache is back within its capacity. Contents too big to
+    /// fit at all aren't kept.
+    pub(crate) fn insert(&mut self, local_path: &Path, contents: String) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        if let CacheCapacity::Bytes(max) = capacity {
+            if contents.len() > max {
+                return;
+            }
+        }
+        self.remove(local_path);
+        self.stats.bytes += contents.len();
+        self.entries.insert(local_path.to_path_buf(), contents);
+        while self.over(capacity) {
+            let Some((_, evicted)) = self.entries.shift_remove_index(0) else {
+                break;
+            };
+            self.stats.bytes -= evicted.len();
+            self.stats.evictions += 1;
+        }
+        self.stats.notes = self.entries.len();
+    }
+
+    /// Drops a note's contents, such as when the note has changed.
+    pub(crate) fn remove(&mut self, local_path: &Path) {
+        if let Some(contents) = self.entries.shift_remove(local_path) {
+            self.stats.bytes -= contents.len();
+            self.stats.notes = self.entries.len();
+        }
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn over(&self, capacity: CacheCapacity) -> bool {
+        match capacity {
+            CacheCapacity::Notes(max) => self.entries.len() > max,
+            CacheCapacity::Bytes(max) => self.stats.bytes > max,
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use ariadne::{Color, Label, Report, ReportKind, Source};
 use chumsky::prelude::*;
-use std::{collections::HashMap, env, fs};
+use chrono::{ DateTime, Utc };
+use glob::{ MatchOptions, Pattern };
+use std::{cmp::Ordering, collections::{ HashMap, HashSet }, env, fs, path::{ Path, MAIN_SEPARATOR_STR }};
 
-use crate::{ VaultIndex };
+use crate::{ VaultIndex, NoteItem, Property };
 
 #[derive(Debug)]
 pub enum QueryOutput {
@@ -11,12 +13,157 @@ pub enum QueryOutput {
     Err(Vec<String>),
 }
 
+/// A format to render a [`QueryOutput`] in. See
+/// [`VaultIndex::query_as`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// See [`QueryOutput::to_json`].
+    Json,
+    /// See [`QueryOutput::to_markdown`].
+    Markdown,
+    /// See [`QueryOutput::to_csv`].
+    Csv,
+}
+
+impl QueryOutput {
+    /// Renders the output in the given format.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Markdown => self.to_markdown(),
+            OutputFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// Renders the output as JSON: a list becomes an array of objects
+    /// with a `file` key (and an `info` key, if the item has any), and a
+    /// table becomes an array of objects keyed by its headers. Errors are
+    /// rendered as an object with an `errors` array.
+    pub fn to_json(&self) -> String {
+        let json = match self {
+            QueryOutput::List(items) => serde_json::Value::Array(items.iter()
+                .map(|item| {
+                    let mut object = serde_json::Map::new();
+                    object.insert("file".to_string(), serde_json::json!(item.note_name));
+                    if let Some(info) = &item.additional_info {
+                        object.insert("info".to_string(), serde_json::json!(info));
+                    }
+                    serde_json::Value::Object(object)
+                })
+                .collect()),
+            QueryOutput::Table(table) => serde_json::Value::Array(table.rows.iter()
+                .map(|row| serde_json::Value::Object(table.head.iter()
+                    .zip(row)
+                    .map(|(head, cell)| (head.clone(), serde_json::json!(cell)))
+                    .collect()))
+                .collect()),
+            QueryOutput::Err(errors) => serde_json::json!({ "errors": errors }),
+        };
+        json.to_string()
+    }
+
+    /// Looks up the notes matched by the query in the index it was run
+    /// against, in the order they appear in the output. For tables, this
+    /// is the note in the `File` column of each row. Errors have no notes.
+    pub fn resolve_notes<'a>(&self, index: &'a VaultIndex) -> Vec<&'a NoteItem> {
+        match self {
+            QueryOutput::List(items) => items.iter()
+                .filter_map(|item| index.notes.get(item.note_name.as_ref()?))
+                .collect(),
+            QueryOutput::Table(table) => table.rows.iter()
+                .filter_map(|row| index.notes.get(row.first()?))
+                .collect(),
+            QueryOutput::Err(_) => vec![],
+        }
+    }
+
+    /// Renders the output as markdown: a list becomes a bulleted list of
+    /// wikilinks, and a table becomes a markdown table with a wikilink in
+    /// its `File` column. Errors are rendered one per line.
+    pub fn to_markdown(&self) -> String {
+        match self {
+            QueryOutput::List(items) => items.iter()
+                .filter_map(|item| {
+                    let link = item.as_wikilink()?;
+                    Some(match &item.additional_info {
+                        Some(info) => format!("- {}: {}\n", link, info),
+                        None => format!("- {}\n", link),
+                    })
+                })
+                .collect(),
+            QueryOutput::Table(table) => {
+                let mut out_string = markdown_row(table.head.iter().map(String::as_str));
+                out_string.push_str(&markdown_row(table.head.iter().map(|_| "---")));
+                for row in &table.rows {
+                    let mut cells: Vec<String> = row.clone();
+                    if let Some(file) = cells.first_mut() {
+                        *file = wikilink(file);
+                    }
+                    out_string.push_str(&markdown_row(cells.iter().map(String::as_str)));
+                }
+                out_string
+            },
+            QueryOutput::Err(errors) => errors.iter().map(|e| format!("{}\n", e)).collect(),
+        }
+    }
+
+    /// Renders the output as CSV, following RFC 4180. A table keeps its
+    /// header row, and a list becomes a single `File` column. Errors
+    /// render as an empty string.
+    pub fn to_csv(&self) -> String {
+        match self {
+            QueryOutput::List(items) => {
+                let mut out_string = csv_row(["File"].into_iter());
+                for item in items {
+                    out_string.push_str(&csv_row([item.note_name.as_deref().unwrap_or_default()].into_iter()));
+                }
+                out_string
+            },
+            QueryOutput::Table(table) => {
+                let mut out_string = csv_row(table.head.iter().map(String::as_str));
+                for row in &table.rows {
+                    out_string.push_str(&csv_row(row.iter().map(String::as_str)));
+                }
+                out_string
+            },
+            QueryOutput::Err(_) => String::new(),
+        }
+    }
+}
+
+fn csv_row<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: Vec<String> = cells
+        .map(|c| if c.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", c.replace('"', "\"\""))
+        } else {
+            c.to_string()
+        })
+        .collect();
+    format!("{}\r\n", cells.join(","))
+}
+
+fn wikilink(note_name: &str) -> String {
+    format!("[[{}]]", note_name.replace('\\', "/"))
+}
+
+fn markdown_row<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: Vec<String> = cells.map(|c| c.replace('|', "\\|").replace('\n', " ")).collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
 #[derive(Debug)]
 pub struct ListItem {
     pub note_name: Option<String>,
     pub additional_info: Option<String>
 }
 
+impl ListItem {
+    /// Returns the item's note as an Obsidian wikilink (`[[Note]]`).
+    pub fn as_wikilink(&self) -> Option<String> {
+        self.note_name.as_deref().map(wikilink)
+    }
+}
+
 #[derive(Debug)]
 pub struct Table {
     pub head: Vec<String>,
@@ -43,7 +190,7 @@ impl QueryStruct {
             as_statements: vec![],
         }
     }
-    pub fn build_output(&self) -> QueryOutput {
+    pub fn build_output(&self, index: &VaultIndex) -> QueryOutput {
         match self.output_type {
             QueryStructType::List => {
                 let mut out_vec: Vec<ListItem> = vec![];
@@ -57,12 +204,84 @@ impl QueryStruct {
                 }
                 
                 
-                return QueryOutput::List(out_vec);
+                QueryOutput::List(out_vec)
             },
             QueryStructType::Table => {
-                todo!("Tables aren't implemented yet!");
+                let mut head: Vec<String> = vec!["File".to_string()];
+                for (i, field) in self.additional_info.iter().enumerate() {
+                    match self.as_statements.get(i) {
+                        Some(Some(alias)) => head.push(alias.clone()),
+                        _ => head.push(field.clone()),
+                    }
+                }
+                let mut rows: Vec<Vec<String>> = vec![];
+                if let Some(matches) = &self.matches {
+                    for note_name in matches {
+                        let mut row: Vec<String> = vec![note_name.to_string()];
+                        if let Some(note) = index.notes.get(note_name) {
+                            for field in &self.additional_info {
+                                row.push(match get_field(note, field) {
+                                    Some(prop) => prop.as_display_string(),
+                                    None => String::new(),
+                                });
+                            }
+                        }
+                        rows.push(row);
+                    }
+                }
+
+                QueryOutput::Table(Table { head, rows })
             },
-        };
+        }
+    }
+}
+
+/// Resolves a field name against a note. Names beginning with `file.`
+/// refer to Dataview's implicit fields; anything else is looked up in
+/// the note's front matter.
+fn get_field(note: &NoteItem, field: &str) -> Option<Property> {
+    match field {
+        "file.name" => Some(Property::Text(note.name.clone())),
+        "file.folder" => {
+            let folder: Vec<String> = match note.local_path.parent() {
+                Some(parent) => parent.iter().map(|c| c.to_string_lossy().to_string()).collect(),
+                None => vec![],
+            };
+            Some(Property::Text(folder.join("/")))
+        },
+        "file.path" => {
+            let path: Vec<String> = note.local_path.iter().map(|c| c.to_string_lossy().to_string()).collect();
+            Some(Property::Text(format!("{}.{}", path.join("/"), note.file_type)))
+        },
+        "file.ext" => Some(Property::Text(note.file_type.clone())),
+        "file.tags" => Some(Property::List(note.tags.iter().map(|t| Property::Text(format!("#{}", t))).collect())),
+        "file.mtime" => note.modified.map(|m| Property::Date(DateTime::<Utc>::from(m))),
+        _ => note.properties.get(field).cloned(),
+    }
+}
+
+fn compare_properties(x: &Property, y: &Property) -> Option<Ordering> {
+    match (x, y) {
+        (Property::Text(a), Property::Text(b)) => Some(a.cmp(b)),
+        (Property::Number(a), Property::Number(b)) => a.partial_cmp(b),
+        (Property::Checkbox(a), Property::Checkbox(b)) => Some(a.cmp(b)),
+        (Property::Date(a), Property::Date(b)) => Some(a.cmp(b)),
+        // Dates can be compared against text such as "2024-01-31".
+        (Property::Date(a), Property::Text(_)) => Some(a.cmp(&y.as_date()?)),
+        (Property::Text(_), Property::Date(b)) => Some(x.as_date()?.cmp(b)),
+        _ => None,
+    }
+}
+
+fn is_truthy(prop: &Option<Property>) -> bool {
+    match prop {
+        Some(Property::Text(t)) => !t.is_empty(),
+        Some(Property::Number(n)) => *n != 0.0,
+        Some(Property::Checkbox(b)) => *b,
+        Some(Property::List(l)) => !l.is_empty(),
+        Some(Property::Map(m)) => !m.is_empty(),
+        Some(Property::Date(_)) => true,
+        Some(Property::Unknown) | None => false,
     }
 }
 
@@ -76,6 +295,17 @@ enum DataSource {
 }
 impl DataSource {
     pub fn get_matches(&self, index: &VaultIndex) -> Option<Vec<String>> {
+        if let DataSource::Folder(folder) = self {
+            return folder_matches(folder, index);
+        }
+        if let DataSource::Tag(tag_name) = self {
+            if let Some(parent) = tag_name.strip_suffix("/**") {
+                return tag_wildcard_matches(parent, true, index);
+            }
+            if let Some(parent) = tag_name.strip_suffix("/*") {
+                return tag_wildcard_matches(parent, false, index);
+            }
+        }
         if let Some(v) = match self {
             DataSource::Tag(tag_name) => index.tags.get(tag_name),
             _ => todo!("Other sources aren't implemented yet!"),
@@ -87,6 +317,87 @@ impl DataSource {
     }
 }
 
+/// Finds the notes in a folder (or its subfolders), or the note at a
+/// path, which may be a glob such as `"Projects/**/active"`.
+fn folder_matches(folder: &str, index: &VaultIndex) -> Option<Vec<String>> {
+    let matched: Vec<String> = index.notes.iter()
+        .filter(|(_, note)| in_folder(note, folder))
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    if matched.is_empty() {
+        None
+    } else {
+        Some(matched)
+    }
+}
+
+/// Finds the notes filed under a sub-tag of `parent`: any tag below it
+/// with `recursive` (`#parent/**`), otherwise just one level down.
+fn tag_wildcard_matches(parent: &str, recursive: bool, index: &VaultIndex) -> Option<Vec<String>> {
+    let prefix = format!("{}/", parent);
+    let mut matched: HashSet<&String> = HashSet::new();
+    for (tag, notes) in &index.tags {
+        let Some(rest) = tag.strip_prefix(&prefix) else {
+            continue;
+        };
+        if recursive || !rest.contains('/') {
+            matched.extend(notes);
+        }
+    }
+
+    let matched: Vec<String> = index.notes.keys()
+        .filter(|key| matched.contains(key))
+        .cloned()
+        .collect();
+    if matched.is_empty() {
+        None
+    } else {
+        Some(matched)
+    }
+}
+
+/// Checks whether a note is matched by a folder source; see
+/// [`folder_matches`].
+fn in_folder(note: &NoteItem, folder: &str) -> bool {
+    let folder = folder.trim_end_matches(['/', '\\']);
+    if is_glob(folder) {
+        let Ok(pattern) = Pattern::new(&folder.replace('\\', "/")) else {
+            return false;
+        };
+        let options = MatchOptions { require_literal_separator: true, ..MatchOptions::new() };
+        note.local_path.ancestors()
+            .filter(|a| !a.as_os_str().is_empty())
+            .any(|a| pattern.matches_with(&a.to_string_lossy().replace('\\', "/"), options))
+    } else {
+        note.local_path.starts_with(Path::new(&folder.replace(['/', '\\'], MAIN_SEPARATOR_STR)))
+    }
+}
+
+/// Checks whether a note is matched by a `FROM` expression, one note at
+/// a time, for [`query_iter`].
+fn in_source(expr: &Expr, note: &NoteItem) -> bool {
+    match expr {
+        Expr::From(x) => in_source(x, note),
+        Expr::Source(DataSource::Tag(tag_name)) => match tag_name.strip_suffix("/**").or_else(|| tag_name.strip_suffix("/*")) {
+            Some(parent) => note.tags.iter().any(|tag| tag.strip_prefix(parent).is_some_and(|rest| rest.starts_with('/'))),
+            None => note.tags.contains(tag_name),
+        },
+        Expr::Source(DataSource::Folder(folder)) => in_folder(note, folder),
+        Expr::Or(x, y) => in_source(x, note) || in_source(y, note),
+        Expr::And(x, y) => in_source(x, note) && in_source(y, note),
+        Expr::Negate(x) => !in_source(x, note),
+        _ => false,
+    }
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Every source, whether a tag, folder or nested expression, evaluates
+/// to the keys of the notes it matches, so sources of different kinds
+/// combine freely, as in `FROM "Folder A" AND #test`.
 fn eval_or(x: Option<Vec<String>>, y: Option<Vec<String>>) -> Option<Vec<String>> {  
     let mut out_vec: Vec<String> = vec![];
     if let Some(x_list) = x {
@@ -128,78 +439,389 @@ fn eval_and(x: Option<Vec<String>>, y: Option<Vec<String>>) -> Option<Vec<String
 
 
 
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
 #[derive(Debug)]
 enum Expr {
 	Invalid, 
 	Source(DataSource),
 	From(Box<Expr>),
 	List {
-        from: Box<Expr>
+        from: Option<Box<Expr>>,
+        clauses: Vec<Expr>,
     },
+    Table {
+        fields: Vec<(String, Option<String>)>,
+        from: Option<Box<Expr>>,
+        clauses: Vec<Expr>,
+    },
+    Where(Box<Expr>),
+    Sort(Vec<(String, bool)>),
+    Limit(usize),
+    Field(String),
+    Literal(Property),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    Contains(Box<Expr>, Box<Expr>),
     Or(Box<Expr>, Box<Expr>),
     And(Box<Expr>, Box<Expr>),
     Negate(Box<Expr>),
 }
 
+const RESERVED_WORDS: [&str; 11] = ["FROM", "WHERE", "SORT", "LIMIT", "AND", "and", "OR", "or", "ASC", "DESC", "AS"];
+
 fn parser() -> impl Parser<char, Expr, Error = Simple<char>> {
     let expr = recursive(|expr| {
-        let tag_path = filter(|c: &char| (c.is_alphanumeric() || c == &'/'|| c == &'-'|| c == &'_')).repeated();
+        let tag_path = filter(|c: &char| (c.is_alphanumeric() || c == &'/'|| c == &'-'|| c == &'_'|| c == &'*')).repeated();
 
         let tag = just('#')
             .ignore_then(tag_path)
             .map(|c: Vec<char>| Expr::Source(DataSource::Tag(c.into_iter().collect())))
             .padded();
 
-        let negate = just('!')
-            .ignore_then(tag)
-            .map(|tag| Expr::Negate(Box::new(tag)));
+        let folder = just('"')
+            .ignore_then(filter(|c: &char| *c != '"').repeated())
+            .then_ignore(just('"'))
+            .collect::<String>()
+            .try_map(|folder: String, span| {
+                if is_glob(&folder) {
+                    if let Err(e) = Pattern::new(&folder) {
+                        return Err(Simple::custom(span, format!("Invalid glob pattern: {}", e.msg)));
+                    }
+                }
+                Ok(Expr::Source(DataSource::Folder(folder)))
+            })
+            .padded();
 
         let paren = expr.delimited_by(just('('), just(')'));
 
-        let atom = tag
-            .or(paren)
+        // Any source or parenthesized expression can be negated, as many
+        // times as you like.
+        let atom = just('!').padded()
+            .repeated()
+            .then(tag.or(folder).or(paren))
+            .foldr(|_, atom| Expr::Negate(Box::new(atom)))
             .padded();
 
-        let ope = atom.clone()
-            
+        // AND binds more tightly than OR, so `#a OR #b AND #c` is
+        // `#a OR (#b AND #c)`.
+        let and_ope = atom.clone()
             .then(
                 text::keyword("AND").to(Expr::And as fn(_, _) -> _)
                 .or(text::keyword("and").to(Expr::And as fn(_, _) -> _))
-                .or(text::keyword("OR").to(Expr::Or as fn(_, _) -> _))
-                .or(text::keyword("or").to(Expr::Or as fn(_, _) -> _))
                 .then(atom)
                 .repeated()
             ).foldl(|lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)));
 
-        ope
+        let or_ope = and_ope.clone()
+            .then(
+                text::keyword("OR").to(Expr::Or as fn(_, _) -> _)
+                .or(text::keyword("or").to(Expr::Or as fn(_, _) -> _))
+                .then(and_ope)
+                .repeated()
+            ).foldl(|lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)));
+
+        or_ope
     });
 
+    // Sources separated by commas are OR'd together, so `FROM #a, #b` is
+    // `FROM #a OR #b`.
     let from = text::keyword("FROM")
-            .ignore_then(expr)
+            .ignore_then(expr.separated_by(just(',').padded()).at_least(1))
+            .map(|sources| sources.into_iter()
+                .reduce(|lhs, rhs| Expr::Or(Box::new(lhs), Box::new(rhs)))
+                .expect("FROM has at least one source"))
             .map(|tag| Expr::From(Box::new(tag)))
             .padded();
 
-    let decl = recursive(|decl| {
+    let field_name = text::ident()
+        .try_map(|part: String, span| {
+            if RESERVED_WORDS.contains(&part.as_str()) {
+                Err(Simple::custom(span, format!("Unexpected keyword {}", part)))
+            } else {
+                Ok(part)
+            }
+        })
+        .separated_by(just('.'))
+        .at_least(1)
+        .map(|parts: Vec<String>| parts.join("."));
+
+    let string_literal = just('"')
+        .ignore_then(filter(|c: &char| *c != '"').repeated())
+        .then_ignore(just('"'))
+        .collect::<String>();
+
+    let number_literal = just('-').or_not()
+        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit()).repeated().at_least(1))
+        .chain::<char, _, _>(just('.').chain(filter(|c: &char| c.is_ascii_digit()).repeated().at_least(1)).or_not().flatten())
+        .collect::<String>()
+        .map(|n| Property::Number(n.parse::<f64>().expect("FAILED TO PARSE FLOAT")));
+
+    let literal = string_literal
+        .map(Property::Text)
+        .or(number_literal)
+        .or(text::keyword("true").to(Property::Checkbox(true)))
+        .or(text::keyword("false").to(Property::Checkbox(false)))
+        .map(Expr::Literal);
+
+    let value = literal.or(field_name.map(Expr::Field)).padded();
+
+    let compare_op = just("<=").to(CompareOp::LtEq)
+        .or(just(">=").to(CompareOp::GtEq))
+        .or(just("!=").to(CompareOp::NotEq))
+        .or(just("=").to(CompareOp::Eq))
+        .or(just("<").to(CompareOp::Lt))
+        .or(just(">").to(CompareOp::Gt));
+
+    let condition = recursive(|condition| {
+        let contains = text::keyword("contains")
+            .ignore_then(
+                value.clone()
+                    .then_ignore(just(','))
+                    .then(value.clone())
+                    .delimited_by(just('('), just(')'))
+            )
+            .map(|(haystack, needle)| Expr::Contains(Box::new(haystack), Box::new(needle)));
+
+        let comparison = value.clone()
+            .then(compare_op.then(value.clone()).or_not())
+            .map(|(lhs, rhs)| match rhs {
+                Some((op, rhs)) => Expr::Compare(Box::new(lhs), op, Box::new(rhs)),
+                None => lhs,
+            });
+
+        let atom = contains
+            .or(condition.delimited_by(just('('), just(')')))
+            .or(comparison)
+            .padded();
+
+        let and_chain = atom.clone()
+            .then(
+                text::keyword("AND").or(text::keyword("and"))
+                .ignore_then(atom)
+                .repeated()
+            ).foldl(|lhs, rhs| Expr::And(Box::new(lhs), Box::new(rhs)));
+
+        and_chain.clone()
+            .then(
+                text::keyword("OR").or(text::keyword("or"))
+                .ignore_then(and_chain)
+                .repeated()
+            ).foldl(|lhs, rhs| Expr::Or(Box::new(lhs), Box::new(rhs)))
+    });
+
+    let r#where = text::keyword("WHERE")
+        .ignore_then(condition)
+        .map(|condition| Expr::Where(Box::new(condition)))
+        .padded();
+
+    let sort_key = field_name
+        .then(
+            text::keyword("ASC").to(true)
+            .or(text::keyword("DESC").to(false))
+            .padded()
+            .or_not()
+        )
+        .map(|(field, ascending)| (field, ascending.unwrap_or(true)))
+        .padded();
+
+    let sort = text::keyword("SORT")
+        .ignore_then(sort_key.separated_by(just(',')).at_least(1))
+        .map(Expr::Sort)
+        .padded();
+
+    let limit = text::keyword("LIMIT")
+        .ignore_then(text::int(10).padded())
+        .try_map(|n: String, span| n.parse::<usize>()
+            .map(Expr::Limit)
+            .map_err(|e| Simple::custom(span, format!("Invalid limit: {}", e))))
+        .padded();
+
+    let clauses = r#where.or(sort).or(limit).repeated();
+
+    let decl = recursive(|_decl| {
         let r#list = text::keyword("LIST")
-            .ignore_then(from)
-            .map(|from| Expr::List {
-                from: Box::new(from)
+            .ignore_then(from.clone().or_not())
+            .then(clauses.clone())
+            .map(|(from, clauses)| Expr::List {
+                from: from.map(Box::new),
+                clauses,
+            });
+
+        let table_field = field_name
+            .then(
+                text::keyword("AS")
+                    .padded()
+                    .ignore_then(string_literal)
+                    .or_not()
+            )
+            .padded();
+
+        let table = text::keyword("TABLE")
+            .ignore_then(table_field.separated_by(just(',')))
+            .then(from.or_not())
+            .then(clauses)
+            .map(|((fields, from), clauses)| Expr::Table {
+                fields,
+                from: from.map(Box::new),
+                clauses,
             });
 
         r#list
+            .or(table)
             // Must be later in the chain than `r#let` to avoid ambiguity
             .padded()
     });
 
-    decl
+    decl.then_ignore(end())
+}
+
+fn eval_condition(expr: &Expr, note: &NoteItem) -> Result<bool, String> {
+    match expr {
+        Expr::And(x, y) => Ok(eval_condition(x, note)? && eval_condition(y, note)?),
+        Expr::Or(x, y) => Ok(eval_condition(x, note)? || eval_condition(y, note)?),
+        Expr::Compare(x, op, y) => {
+            let ordering = match (eval_value(x, note)?, eval_value(y, note)?) {
+                (Some(lhs), Some(rhs)) => compare_properties(&lhs, &rhs),
+                _ => None,
+            };
+            Ok(match op {
+                CompareOp::Eq => ordering == Some(Ordering::Equal),
+                CompareOp::NotEq => ordering != Some(Ordering::Equal),
+                CompareOp::Lt => ordering == Some(Ordering::Less),
+                CompareOp::LtEq => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                CompareOp::Gt => ordering == Some(Ordering::Greater),
+                CompareOp::GtEq => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            })
+        },
+        Expr::Contains(haystack, needle) => {
+            let needle = match eval_value(needle, note)? {
+                Some(n) => n,
+                None => return Ok(false),
+            };
+            Ok(match eval_value(haystack, note)? {
+                Some(Property::List(items)) => items.iter().any(|i| compare_properties(i, &needle) == Some(Ordering::Equal)),
+                Some(Property::Text(text)) => match &needle {
+                    Property::Text(n) => text.contains(n.as_str()),
+                    _ => false,
+                },
+                Some(other) => compare_properties(&other, &needle) == Some(Ordering::Equal),
+                None => false,
+            })
+        },
+        _ => Ok(is_truthy(&eval_value(expr, note)?)),
+    }
+}
+
+/// Checks that a `WHERE` condition can be evaluated, without a note to
+/// evaluate it against. Gives the error [`eval_condition`] would.
+fn check_condition(expr: &Expr) -> Result<(), String> {
+    match expr {
+        Expr::And(x, y) | Expr::Or(x, y) => {
+            check_condition(x)?;
+            check_condition(y)
+        },
+        Expr::Compare(x, _, y) | Expr::Contains(x, y) => {
+            check_value(x)?;
+            check_value(y)
+        },
+        _ => check_value(expr),
+    }
+}
+
+fn check_value(expr: &Expr) -> Result<(), String> {
+    match expr {
+        Expr::Field(_) | Expr::Literal(_) => Ok(()),
+        _ => Err(format!("Expected a field or a value, found {:?}", expr)),
+    }
+}
+
+fn eval_value(expr: &Expr, note: &NoteItem) -> Result<Option<Property>, String> {
+    match expr {
+        Expr::Field(field) => Ok(get_field(note, field)),
+        Expr::Literal(prop) => Ok(Some(prop.clone())),
+        _ => Err(format!("Expected a field or a value, found {:?}", expr)),
+    }
+}
+
+fn eval_clauses(clauses: &[Expr], index: &VaultIndex, query_struct: &mut QueryStruct) -> Result<(), String> {
+    let mut matches: Vec<String> = query_struct.matches.clone().unwrap_or_default();
+    for clause in clauses {
+        match clause {
+            Expr::Where(condition) => {
+                let mut filtered: Vec<String> = vec![];
+                for note_name in matches {
+                    if let Some(note) = index.notes.get(&note_name) {
+                        if eval_condition(condition, note)? {
+                            filtered.push(note_name);
+                        }
+                    }
+                }
+                matches = filtered;
+            },
+            Expr::Sort(keys) => sort_matches(&mut matches, keys, index),
+            Expr::Limit(limit) => matches.truncate(*limit),
+            _ => return Err(format!("Unexpected clause {:?}", clause)),
+        }
+    }
+    query_struct.matches = Some(matches);
+    Ok(())
+}
+
+fn sort_matches(matches: &mut [String], keys: &[(String, bool)], index: &VaultIndex) {
+    matches.sort_by(|x, y| {
+        for (field, ascending) in keys {
+            let x_val = index.notes.get(x).and_then(|n| get_field(n, field));
+            let y_val = index.notes.get(y).and_then(|n| get_field(n, field));
+            // Notes missing the field always go last.
+            let ordering = match (&x_val, &y_val) {
+                (Some(a), Some(b)) => {
+                    let o = compare_properties(a, b).unwrap_or(Ordering::Equal);
+                    if *ascending { o } else { o.reverse() }
+                },
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
 }
 
 fn eval<'a>(expr: &'a Expr, index: &'a VaultIndex, query_struct: &'a mut  QueryStruct) -> Result<Option<Vec<String>>, String> {
 
     match expr {
-        Expr::List {from} => {
+        Expr::List {from, clauses} => {
             query_struct.output_type = QueryStructType::List;
-            return Ok(eval(from, &index, query_struct)?);
+            if let Some(from) = from {
+                eval(from, index, query_struct)?;
+            } else {
+                query_struct.matches = Some(index.notes.keys().cloned().collect());
+            }
+            eval_clauses(clauses, index, query_struct)?;
+            Ok(query_struct.matches.clone())
+        },
+        Expr::Table {fields, from, clauses} => {
+            query_struct.output_type = QueryStructType::Table;
+            query_struct.additional_info = fields.iter().map(|(field, _)| field.clone()).collect();
+            query_struct.as_statements = fields.iter().map(|(_, alias)| alias.clone()).collect();
+            if let Some(from) = from {
+                eval(from, index, query_struct)?;
+            } else {
+                query_struct.matches = Some(index.notes.keys().cloned().collect());
+            }
+            eval_clauses(clauses, index, query_struct)?;
+            Ok(query_struct.matches.clone())
         },
         Expr::From(tag) => {
             let m = eval(tag, &index, query_struct)?;
@@ -209,17 +831,62 @@ fn eval<'a>(expr: &'a Expr, index: &'a VaultIndex, query_struct: &'a mut  QueryS
         Expr::Source(source) => Ok(source.get_matches(&index)),
         Expr::Or(x, y) => Ok(eval_or(eval(x, &index, query_struct)?, eval(y, &index, query_struct)?)),
         Expr::And(x, y) => Ok(eval_and(eval(x, &index, query_struct)?, eval(y, &index, query_struct)?)),
+        Expr::Negate(x) => {
+            let excluded = eval(x, index, query_struct)?.unwrap_or_default();
+            let out_vec: Vec<String> = index.notes.keys()
+                .filter(|key| !excluded.contains(key))
+                .cloned()
+                .collect();
+            if out_vec.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(out_vec))
+            }
+        },
         
         _ => todo!("Stuff here!"),
     }
 }
 
+/// Runs a query lazily, yielding the notes it matches one at a time.
+/// Tables yield the note of each row.
+pub fn query_iter<'a>(in_query: &str, index: &'a VaultIndex) -> Result<impl Iterator<Item = ListItem> + 'a, Vec<String>> {
+    let (from, clauses) = match parser().parse(in_query) {
+        Ok(Expr::List { from, clauses }) | Ok(Expr::Table { from, clauses, .. }) => (from, clauses),
+        Ok(_) => return Err(vec!["Only LIST and TABLE queries can be iterated over!".to_string()]),
+        Err(err_vec) => return Err(err_vec.into_iter().map(|e| format!("{}", e)).collect()),
+    };
+    let mut items: Box<dyn Iterator<Item = String> + 'a> = Box::new(index.notes.iter()
+        .filter(move |(_, note)| from.as_ref().is_none_or(|from| in_source(from, note)))
+        .map(|(key, _)| key.clone()));
+    for clause in clauses {
+        items = match clause {
+            Expr::Where(condition) => {
+                check_condition(&condition).map_err(|eval_err| vec![eval_err])?;
+                Box::new(items.filter(move |note_name| index.notes.get(note_name)
+                    .is_some_and(|note| eval_condition(&condition, note).unwrap_or(false))))
+            },
+            Expr::Sort(keys) => {
+                let mut sorted: Vec<String> = items.collect();
+                sort_matches(&mut sorted, &keys, index);
+                Box::new(sorted.into_iter())
+            },
+            Expr::Limit(limit) => Box::new(items.take(limit)),
+            _ => return Err(vec![format!("Unexpected clause {:?}", clause)]),
+        };
+    }
+    Ok(items.map(|note_name| ListItem {
+        note_name: Some(note_name),
+        additional_info: None,
+    }))
+}
+
 pub fn to_view(in_query: &str, index: &VaultIndex) -> QueryOutput {
     let mut query_struct = QueryStruct::new();
 	match parser().parse_recovery_verbose(in_query) {
         (Some(ast), _err_vec) => match eval(&ast, &index, &mut query_struct) {
             Ok(output) => {
-                return query_struct.build_output();
+                return query_struct.build_output(index);
             },
             Err(eval_err) => QueryOutput::Err(vec![format!("{}", eval_err)]),
         },
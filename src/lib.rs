@@ -26,33 +26,198 @@
 //! let fc = vi.get_item("This is the Test Vault");
 //! assert_eq!(vec!["test".to_string()], fc.unwrap().unwrap_note().tags);
 //! ```
+mod content_cache;
 mod dataview;
+mod markdown;
+mod obsidian;
+mod schema;
+mod source;
+#[cfg(feature = "tokio")]
+mod async_vault;
+#[cfg(feature = "bincode")]
+mod cache;
+#[cfg(feature = "zip")]
+mod zip_vault;
 
-use std::io;
-use std::fs::{ self };
-use std::path::{ PathBuf };
-use std::collections::HashMap;
-use chrono::{ DateTime, Utc, serde::ts_seconds };
+use std::io::{ self, BufRead, BufReader };
+use std::fs::{ self, File };
+use std::path::{ Path, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR };
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::collections::{ HashMap, HashSet };
+use std::fmt::{ self, Write as _ };
+use std::ops::Range;
+use std::sync::{ Arc, Mutex };
+use std::time::SystemTime;
+use chrono::{ DateTime, NaiveDate, NaiveDateTime, Utc, serde::ts_seconds };
 use indexmap::{ IndexMap };
 use regex::Regex;
-use yaml_rust::{ YamlLoader, Yaml };
-use serde::{ Deserialize, Serialize };
+use yaml_rust::{ YamlEmitter, YamlLoader, Yaml, yaml::Hash as YamlHash };
+use yaml_rust::parser::{ Event, EventReceiver, Parser as YamlParser };
+use serde::{ Deserialize, Serialize, Serializer };
+use unicode_normalization::{ UnicodeNormalization, char::is_combining_mark };
 
-pub use dataview::{QueryOutput, ListItem, Table};
+pub use content_cache::{ CacheCapacity, CacheStats };
+pub use dataview::{QueryOutput, ListItem, OutputFormat, Table};
+pub use markdown::Callout;
+pub use obsidian::{ Bookmark, GraphColor, GraphColorGroup, GraphSettings };
+pub use schema::{ PropertyKind, PropertyRule, Schema, SchemaViolation, ViolationReason };
+pub use source::{ StdFs, VaultEntry, VaultMetadata, VaultSource };
+#[cfg(feature = "memmap2")]
+pub use memmap2::Mmap;
+use content_cache::ContentCache;
+use source::SharedSource;
 
 
 /// Represents a property in a note's front matter.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Properties are equal when they're the same kind of value and hold the
+/// same thing: numbers are compared as `f64`s, dates by the instant they
+/// refer to, and lists and maps item by item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Property {
     Text(String),
     Number(f64),
     Checkbox(bool),
     List(Vec<Property>),
+    Map(IndexMap<String, Property>),
     #[serde(with = "ts_seconds")]
     Date(DateTime<Utc>),
     Unknown,
 }
+impl Property {
+    /// Formats the property as a plain string for display. Lists are
+    /// joined with commas (recursively), maps become `key: value` pairs
+    /// in their original order, dates are formatted as ISO 8601 and
+    /// unknown values become an empty string.
+    pub fn as_display_string(&self) -> String {
+        match self {
+            Property::Text(t) => t.clone(),
+            Property::Number(n) => n.to_string(),
+            Property::Checkbox(b) => b.to_string(),
+            Property::List(l) => l.iter().map(|p| p.as_display_string()).collect::<Vec<String>>().join(", "),
+            Property::Map(m) => m.iter().map(|(k, v)| format!("{}: {}", k, v.as_display_string())).collect::<Vec<String>>().join(", "),
+            Property::Date(d) => d.to_rfc3339(),
+            Property::Unknown => String::new(),
+        }
+    }
+
+    /// Converts the property to JSON, as for
+    /// [`NoteItem::properties_as_json_with`].
+    fn to_json_with(&self, opts: &PropertyJsonOpts) -> serde_json::Value {
+        match self {
+            Property::Date(d) => match &opts.date_format {
+                Some(format) => {
+                    let mut formatted = String::new();
+                    if write!(formatted, "{}", d.format(format)).is_err() {
+                        formatted = d.to_rfc3339();
+                    }
+                    serde_json::Value::String(formatted)
+                },
+                None => serde_json::Value::from(d.timestamp()),
+            },
+            Property::List(l) => {
+                let is_flat = l.iter().all(|p| !matches!(p, Property::List(_) | Property::Map(_)));
+                match &opts.list_separator {
+                    Some(separator) if is_flat => {
+                        let items: Vec<String> = l.iter()
+                            .map(|p| match p.to_json_with(opts) {
+                                serde_json::Value::String(s) => s,
+                                serde_json::Value::Null => String::new(),
+                                other => other.to_string(),
+                            })
+                            .collect();
+                        serde_json::Value::String(items.join(separator))
+                    },
+                    _ => serde_json::Value::Array(l.iter().map(|p| p.to_json_with(opts)).collect()),
+                }
+            },
+            Property::Map(m) => serde_json::Value::Object(m.iter().map(|(k, v)| (k.clone(), v.to_json_with(opts))).collect()),
+            _ => serde_json::to_value(self).unwrap_or_default(),
+        }
+    }
+
+    /// Reads the property as a date. Front matter dates are written as
+    /// text, so along with [`Property::Date`] this accepts text in ISO 8601
+    /// form, either a full timestamp or a plain date (`2024-01-31`, taken
+    /// as midnight UTC) or a date and time without a timezone (taken as
+    /// UTC).
+    pub fn as_date(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Property::Date(d) => Some(*d),
+            Property::Text(t) => {
+                let t = t.trim();
+                if let Ok(d) = DateTime::parse_from_rfc3339(t) {
+                    return Some(d.with_timezone(&Utc));
+                }
+                for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+                    if let Ok(d) = NaiveDateTime::parse_from_str(t, format) {
+                        return Some(d.and_utc());
+                    }
+                }
+                NaiveDate::parse_from_str(t, "%Y-%m-%d").ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|d| d.and_utc())
+            },
+            _ => None,
+        }
+    }
+
+    /// Describes how long before or after `now` the property's date is,
+    /// in words: "just now", "5 minutes ago", "in 3 hours", "yesterday",
+    /// "tomorrow", "in 2 days", "3 weeks ago", "in 4 months" or "2 years
+    /// ago". Anything a day or more away is counted in calendar days.
+    /// Returns `None` if the property isn't a date; see
+    /// [`Property::as_date`].
+    pub fn humanize_relative(&self, now: DateTime<Utc>) -> Option<String> {
+        fn phrase(count: i64, unit: &str, future: bool) -> String {
+            let plural = if count == 1 { "" } else { "s" };
+            if future {
+                format!("in {} {}{}", count, unit, plural)
+            } else {
+                format!("{} {}{} ago", count, unit, plural)
+            }
+        }
+        let date = self.as_date()?;
+        let seconds = (date - now).num_seconds();
+        let future = seconds > 0;
+        let seconds = seconds.abs();
+        if seconds < 60 {
+            return Some("just now".to_string());
+        }
+        if seconds < 60 * 60 {
+            return Some(phrase(seconds / 60, "minute", future));
+        }
+        if seconds < 24 * 60 * 60 {
+            return Some(phrase(seconds / (60 * 60), "hour", future));
+        }
+        let days = (date.date_naive() - now.date_naive()).num_days().abs();
+        Some(match days {
+            1 if future => "tomorrow".to_string(),
+            1 => "yesterday".to_string(),
+            0..=6 => phrase(days, "day", future),
+            7..=29 => phrase(days / 7, "week", future),
+            30..=364 => phrase(days / 30, "month", future),
+            _ => phrase(days / 365, "year", future),
+        })
+    }
+
+    /// Converts the property to a YAML value, for writing it back into a
+    /// note's front matter. Dates are written as ISO 8601 strings.
+    pub(crate) fn to_yaml(&self) -> Yaml {
+        match self {
+            Property::Text(t) => Yaml::String(t.clone()),
+            Property::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => Yaml::Integer(*n as i64),
+            Property::Number(n) => Yaml::Real(n.to_string()),
+            Property::Checkbox(b) => Yaml::Boolean(*b),
+            Property::List(l) => Yaml::Array(l.iter().map(|p| p.to_yaml()).collect()),
+            Property::Map(m) => Yaml::Hash(m.iter().map(|(k, v)| (Yaml::String(k.clone()), v.to_yaml())).collect()),
+            Property::Date(d) => Yaml::String(d.to_rfc3339()),
+            Property::Unknown => Yaml::Null,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum VaultItem<'a> {
@@ -75,7 +240,7 @@ impl <'a> VaultItem<'a> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemType {
     File,
     Folder,
@@ -83,7 +248,7 @@ pub enum ItemType {
     Root
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TreeNode {
     pub name: String,
     pub index: usize,
@@ -103,7 +268,7 @@ impl TreeNode {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tree {
     arena: Vec<TreeNode>,
 }
@@ -160,6 +325,29 @@ impl Tree {
         nodes
     }
 
+    /// Returns the tree as nested JSON, for handing to a frontend tree
+    /// component. Each node is an object with its `name`, its `type`
+    /// (`"root"`, `"folder"`, `"note"` or `"file"`) and its `children`,
+    /// in tree order.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.node_json(0)).expect("Couldn't parse Tree into JSON!")
+    }
+
+    fn node_json(&self, node_index: usize) -> serde_json::Value {
+        let node = &self.arena[node_index];
+        let item_type = match node.item {
+            ItemType::Root => "root",
+            ItemType::Folder => "folder",
+            ItemType::Note => "note",
+            ItemType::File => "file",
+        };
+        serde_json::json!({
+            "name": node.name,
+            "type": item_type,
+            "children": node.children.iter().map(|child| self.node_json(*child)).collect::<Vec<serde_json::Value>>(),
+        })
+    }
+
     fn add_node(&mut self, name: String, item: ItemType, depth: u32) -> usize {
         let idx = self.arena.len();
         self.arena.push(TreeNode::new(idx, item, name, depth));
@@ -167,11 +355,21 @@ impl Tree {
     }
 }
 
+/// Compares two strings for display order, ignoring case and accents
+/// first (so `é` sorts with `e` rather than after `z`), then falling
+/// back to plain ordering to break ties.
+fn unicode_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let fold = |s: &str| -> String {
+        s.nfd().filter(|c| !is_combining_mark(*c)).flat_map(|c| c.to_lowercase()).collect()
+    };
+    fold(a).cmp(&fold(b)).then_with(|| a.cmp(b))
+}
+
 #[derive(Debug)]
 enum FileFolder {
     File(FileItem),
     Folder(FolderItem),
-    Note(NoteItem),
+    Note(Box<NoteItem>),
 }
 
 /// Represents a folder in the Vault.
@@ -184,48 +382,807 @@ enum FileFolder {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FolderItem {
     pub name: String,
+    #[serde(serialize_with = "serialize_path")]
     pub path: PathBuf,
+    #[serde(serialize_with = "serialize_path")]
     pub local_path: PathBuf,
 }
 
+/// Represents a file in the Vault that isn't a note, such as an image or
+/// PDF. As with [`NoteItem`], `archive` is set when the file was indexed
+/// from a zip archive.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileItem {
     pub name: String,
     pub file_type: String,
+    #[serde(serialize_with = "serialize_path")]
     pub path: PathBuf,
+    #[serde(serialize_with = "serialize_path")]
     pub local_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<PathBuf>,
+    #[serde(skip)]
+    source: Option<SharedSource>,
+}
+impl FileItem {
+    /// Returns the size of the file in bytes, as it is now.
+    pub fn size_bytes(&self) -> Result<u64, io::Error> {
+        item_size(&self.path, self.archive.as_deref(), self.source.as_ref())
+    }
+    /// Reads the file as text, from its archive or custom source if it
+    /// has one.
+    pub(crate) fn read_to_string(&self) -> Result<String, io::Error> {
+        item_contents(&self.path, self.archive.as_deref(), self.source.as_ref())
+    }
+}
+
+/// Serializes a path with `/` as its separator on every platform, so that
+/// JSON written on Windows matches that written elsewhere. Paths in
+/// memory keep the platform's separator.
+fn serialize_path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    let path = path.to_string_lossy();
+    if MAIN_SEPARATOR == '\\' {
+        serializer.serialize_str(&path.replace('\\', "/"))
+    } else {
+        serializer.serialize_str(&path)
+    }
+}
+
+/// Reads the size of a note or file, from its archive or custom source if
+/// it has one.
+#[cfg_attr(not(feature = "zip"), allow(unused_variables))]
+fn item_size(path: &Path, archive: Option<&Path>, source: Option<&SharedSource>) -> Result<u64, io::Error> {
+    #[cfg(feature = "zip")]
+    if let Some(archive) = archive {
+        return zip_vault::entry_size(archive, path);
+    }
+    match source {
+        Some(source) => Ok(source.0.metadata(path)?.len),
+        None => Ok(fs::metadata(path)?.len()),
+    }
+}
+
+/// Reads a note or file as text, from its archive or custom source if it
+/// has one.
+#[cfg_attr(not(feature = "zip"), allow(unused_variables))]
+fn item_contents(path: &Path, archive: Option<&Path>, source: Option<&SharedSource>) -> Result<String, io::Error> {
+    #[cfg(feature = "zip")]
+    if let Some(archive) = archive {
+        return zip_vault::read_entry(archive, path);
+    }
+    match source {
+        Some(source) => source.0.read_to_string(path),
+        None => fs::read_to_string(path),
+    }
 }
 
 /// Represents a note in the Vault.
 ///
 /// The `properties` field represents the properties defined in a note's
-/// front matter, as a HashMap of [`Property`] enums.
+/// front matter, as a map of [`Property`] enums in the order the keys
+/// were written. `tags` holds every tag on the note, expanded into its
+/// parent tags, and `raw_tags` holds them as they were written.
+///
+/// `archive` is set when the note was indexed from a zip archive, in which
+/// case `path` is the note's entry name inside that archive.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NoteItem {
     pub name: String,
+    pub title: String,
     pub file_type: String,
+    #[serde(serialize_with = "serialize_path")]
     pub path: PathBuf,
+    #[serde(serialize_with = "serialize_path")]
     pub local_path: PathBuf,
-    pub properties: HashMap<String, Property>,
+    pub properties: IndexMap<String, Property>,
     pub tags: Vec<String>,
+    pub raw_tags: Vec<String>,
+    pub modified: Option<SystemTime>,
+    pub embeds: Vec<String>,
+    pub links: Vec<String>,
+    /// The key in [`VaultIndex::notes`] of the note each of `links` points
+    /// to, in the same order, or `None` for a link that doesn't point to
+    /// a note. Only filled in with
+    /// [`VaultIndexBuilder::resolve_links_eagerly`]; empty otherwise.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_links: Vec<Option<String>>,
+    pub external_links: Vec<String>,
+    pub blocks: HashMap<String, usize>,
+    pub cssclasses: Vec<String>,
+    pub raw_key: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<PathBuf>,
+    #[serde(skip)]
+    source: Option<SharedSource>,
+    #[serde(skip)]
+    parse_options: ParseOptions,
+    #[serde(skip)]
+    tag_occurrences: Vec<(String, usize, usize)>,
+    #[serde(skip)]
+    frontmatter_span: Option<Range<usize>>,
 }
 impl NoteItem {
+    /// Reads the note from the disk once, returning both its parsed front
+    /// matter and its body.
+    pub fn read(&self) -> Result<ParsedNote, io::Error> {
+        let contents = self.parse_options.normalize(self.get_contents()?);
+        let (frontmatter, body) = markdown::split_frontmatter(&contents);
+        let frontmatter = match frontmatter {
+            Some(yaml) if !yaml.trim().is_empty() => VaultIndex::generate_properties(yaml, &self.parse_options)?.0,
+            _ => Default::default(),
+        };
+        Ok(ParsedNote {
+            frontmatter,
+            body: body.to_string(),
+        })
+    }
     /// Returns a representation of this struct as a json string.
     pub fn as_json(&self) -> String {
         serde_json::to_string(self).expect(&format!("Couldn't parse NoteItem {:?} into JSON!", self.name))
     }
+    /// Like [`NoteItem::as_json`], but leaves out the named properties,
+    /// such as private or noisy ones, from both `properties` and
+    /// `raw_key`.
+    pub fn as_json_excluding(&self, keys: &[&str]) -> String {
+        let mut json = serde_json::to_value(self).expect("Couldn't parse NoteItem into JSON!");
+        for field in ["properties", "raw_key"] {
+            if let Some(map) = json.get_mut(field).and_then(|v| v.as_object_mut()) {
+                for key in keys {
+                    map.remove(*key);
+                }
+            }
+        }
+        json.to_string()
+    }
     /// Returns a representation of this struct's `properties` 
     /// field as a json string.
     pub fn properties_as_json(&self) -> String {
         serde_json::to_string(&self.properties).expect(&format!("Couldn't parse NoteItem {:?} properties into JSON!", self.name))
     }
+    /// Like [`NoteItem::properties_as_json`], but with control over how
+    /// lists and dates are written. See [`PropertyJsonOpts`].
+    pub fn properties_as_json_with(&self, opts: &PropertyJsonOpts) -> String {
+        let properties: IndexMap<&String, serde_json::Value> = self.properties.iter()
+            .map(|(key, value)| (key, value.to_json_with(opts)))
+            .collect();
+        serde_json::to_string(&properties).expect("Couldn't parse NoteItem properties into JSON!")
+    }
+    /// Writes the note's parsed properties as a `---`-delimited YAML
+    /// front matter block, followed by the given body; see
+    /// [`VaultIndex::render_note`] for a body to go with it. Keys are
+    /// written in the order they were in the note, as first written when
+    /// they were normalized (see
+    /// [`VaultIndexBuilder::normalize_property_keys`]).
+    /// A note without properties gets no front matter.
+    pub fn to_markdown_with_frontmatter(&self, body: &str) -> String {
+        if self.properties.is_empty() {
+            return body.to_string();
+        }
+        let yaml_hash: YamlHash = self.properties.iter()
+            .map(|(k, v)| (Yaml::String(self.raw_key.get(k).unwrap_or(k).clone()), v.to_yaml()))
+            .collect();
+        // The emitter starts its output with the opening `---`.
+        let mut yaml_out = String::new();
+        YamlEmitter::new(&mut yaml_out)
+            .dump(&Yaml::Hash(yaml_hash))
+            .expect("Couldn't write properties as YAML!");
+        format!("{}\n---\n{}", yaml_out, body)
+    }
     /// Retrieves the contents of the note from the disk.
     pub fn get_contents(&self) -> Result<String, io::Error> {
-        fs::read_to_string(&self.path)
+        item_contents(&self.path, self.archive.as_deref(), self.source.as_ref())
+    }
+    /// Memory-maps the note's file, giving a view of its bytes without
+    /// reading them into memory first. Handy for searching through very
+    /// large notes.
+    ///
+    /// The map reflects the file as it is on the disk, so if the file is
+    /// changed (or truncated) by another process while the map is alive,
+    /// the bytes may change underneath you, or reading them may crash the
+    /// program. Only use this on files that won't be edited while mapped.
+    ///
+    /// Notes in a zip archive or from a custom [`VaultSource`] can't be
+    /// mapped; use [`NoteItem::get_contents`] for those.
+    #[cfg(feature = "memmap2")]
+    pub fn mmap_contents(&self) -> Result<Mmap, io::Error> {
+        #[cfg(feature = "zip")]
+        if self.archive.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Notes in a zip archive can't be memory-mapped!"));
+        }
+        if self.source.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Notes from a custom VaultSource can't be memory-mapped!"));
+        }
+        let file = File::open(&self.path)?;
+        // Safety: see above; the caller is trusted not to let the file
+        // change while the map is alive.
+        unsafe { Mmap::map(&file) }
+    }
+    /// Returns the size of the note's file in bytes, as it is now.
+    pub fn size_bytes(&self) -> Result<u64, io::Error> {
+        item_size(&self.path, self.archive.as_deref(), self.source.as_ref())
+    }
+    /// Retrieves the contents of the note from the disk as plain text,
+    /// with the front matter and markdown syntax (heading markers,
+    /// emphasis, list bullets, code fences and so on) removed. Links are
+    /// reduced to their text, and embeds are dropped.
+    pub fn as_plaintext(&self) -> Result<String, io::Error> {
+        let contents = self.parse_options.normalize(self.get_contents()?);
+        let (_, body) = markdown::split_frontmatter(&contents);
+        Ok(markdown::to_plaintext(body))
+    }
+    /// Retrieves the contents of the note from the disk and finds its
+    /// callouts (`> [!note] Title` blocks), in the order they appear; see
+    /// [`Callout`].
+    pub fn callouts(&self) -> Result<Vec<Callout>, io::Error> {
+        let contents = self.parse_options.normalize(self.get_contents()?);
+        Ok(markdown::callouts(&contents))
+    }
+    /// Returns the note's aliases, from its `aliases` (or `alias`) front
+    /// matter property, which may be a list or a single string.
+    pub fn aliases(&self) -> Vec<String> {
+        let mut out_aliases: Vec<String> = vec![];
+        for key in ["aliases", "alias"] {
+            let values: Vec<&Property> = match self.properties.get(key) {
+                Some(Property::List(l)) => l.iter().collect(),
+                Some(p) => vec![p],
+                None => vec![],
+            };
+            for value in values {
+                if let Property::Text(t) = value {
+                    if !t.trim().is_empty() {
+                        out_aliases.push(t.trim().to_string());
+                    }
+                }
+            }
+        }
+        out_aliases
+    }
+
+    /// Returns the tags written inline in the note's body, without their
+    /// `#`, in the order they appear. Unlike `raw_tags`, tags in the
+    /// front matter aren't included.
+    pub fn inline_tags(&self) -> Vec<&str> {
+        self.tag_occurrences.iter().map(|(tag, _, _)| tag.as_str()).collect()
+    }
+
+    /// Returns the targets of the `[[...]]` links written in the given
+    /// front matter property, without any heading or display text part.
+    pub fn property_links(&self, key: &str) -> Vec<String> {
+        fn collect(property: &Property, out: &mut Vec<String>) {
+            match property {
+                Property::Text(t) => out.extend(markdown::wikilink_targets(t)),
+                Property::List(l) => l.iter().for_each(|p| collect(p, out)),
+                _ => {},
+            }
+        }
+        let mut out_links: Vec<String> = vec![];
+        if let Some(property) = self.properties.get(key) {
+            collect(property, &mut out_links);
+        }
+        out_links
+    }
+
+    /// Returns each tag written inline in the note's body, without its
+    /// `#`, with the line and column (in characters) of its `#`, from 0.
+    pub fn tag_occurrences(&self) -> Vec<(String, usize, usize)> {
+        self.tag_occurrences.clone()
+    }
+
+    /// Returns the byte range of the note's front matter within its file,
+    /// from the opening `---` to the end of the closing `---` line.
+    pub fn frontmatter_span(&self) -> Option<Range<usize>> {
+        self.frontmatter_span.clone()
+    }
+
+    /// Returns where each of the note's tags (including parent tags) was
+    /// declared: in the front matter `tags` property, inline in the body,
+    /// or both.
+    pub fn tag_sources(&self) -> HashMap<String, TagSource> {
+        let mut sources: HashMap<String, TagSource> = HashMap::new();
+        for tag in VaultIndex::frontmatter_tags(&self.properties) {
+            for split_tag in VaultIndex::tag_splitter(tag) {
+                sources.insert(split_tag, TagSource::Frontmatter);
+            }
+        }
+        for tag in self.inline_tags() {
+            for split_tag in VaultIndex::tag_splitter(tag.to_string()) {
+                sources.entry(split_tag)
+                    .and_modify(|source| if *source == TagSource::Frontmatter { *source = TagSource::Both })
+                    .or_insert(TagSource::Inline);
+            }
+        }
+        sources
+    }
+
+    /// Returns the note's properties merged over those of the folder index
+    /// notes (`index.md` or `_folder.md`) above it; closer ones win.
+    pub fn effective_properties(&self, index: &VaultIndex) -> IndexMap<String, Property> {
+        let mut folders: Vec<&Path> = self.local_path.ancestors().skip(1).collect();
+        folders.reverse();
+        let mut properties: IndexMap<String, Property> = IndexMap::new();
+        for folder in folders {
+            for index_name in FOLDER_INDEX_NAMES {
+                let index_path = folder.join(index_name);
+                let Some(key) = index_path.to_str().and_then(|p| index.filepath_ref.get(p)) else {
+                    continue;
+                };
+                if let Some(index_note) = index.notes.get(key).filter(|n| n.local_path == index_path) {
+                    properties.extend(index_note.properties.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+            }
+        }
+        properties.extend(self.properties.iter().map(|(k, v)| (k.clone(), v.clone())));
+        properties
+    }
+
+    /// Counts the words in the note's body, ignoring front matter and
+    /// markdown syntax. See [`NoteItem::as_plaintext`].
+    pub fn word_count(&self) -> Result<usize, io::Error> {
+        Ok(self.as_plaintext()?.split_whitespace().count())
+    }
+    /// Checks whether the note has nothing but whitespace after its front
+    /// matter (if it has any). This reads the note from the disk; see
+    /// [`VaultIndex::metadata_only_notes`] to take virtual contents into
+    /// account.
+    pub fn is_empty_body(&self) -> Result<bool, io::Error> {
+        let contents = self.parse_options.normalize(self.get_contents()?);
+        let (_, body) = markdown::split_frontmatter(&contents);
+        Ok(body.trim().is_empty())
+    }
+    /// Returns an iterator over the lines of the note, read from the disk
+    /// through a buffered reader rather than all at once.
+    pub fn lines(&self) -> Result<impl Iterator<Item = io::Result<String>>, io::Error> {
+        #[cfg(feature = "zip")]
+        if self.archive.is_some() {
+            let reader: Box<dyn BufRead> = Box::new(io::Cursor::new(self.get_contents()?.into_bytes()));
+            return Ok(reader.lines());
+        }
+        if let Some(source) = &self.source {
+            let reader: Box<dyn BufRead> = Box::new(io::Cursor::new(source.0.read_to_string(&self.path)?.into_bytes()));
+            return Ok(reader.lines());
+        }
+        let reader: Box<dyn BufRead> = Box::new(BufReader::new(File::open(&self.path)?));
+        Ok(reader.lines())
+    }
+}
+
+/// The names of notes whose properties are inherited by the other notes
+/// in their folder, in increasing order of precedence. See
+/// [`NoteItem::effective_properties`].
+const FOLDER_INDEX_NAMES: [&str; 2] = ["index", "_folder"];
+
+/// How many levels of nested embeds [`VaultIndex::render_note`] will
+/// inline before leaving the rest as they are.
+pub const MAX_EMBED_DEPTH: usize = 16;
+
+/// The damping factor used by [`VaultIndex::rank_notes`]: the chance
+/// that a reader follows a link rather than jumping to a random note.
+const RANK_DAMPING: f64 = 0.85;
+
+/// The group that [`VaultIndex::group_by_tag`] puts untagged notes in.
+pub const UNCATEGORIZED_GROUP: &str = "uncategorized";
+
+/// How many rounds [`VaultIndex::rank_notes`] runs for.
+const RANK_ITERATIONS: usize = 50;
+
+/// Where a note's tag was declared. Returned by [`NoteItem::tag_sources`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagSource {
+    Frontmatter,
+    Inline,
+    Both,
+}
+
+/// Where a note's [`title`](NoteItem::title) comes from. Set with
+/// [`VaultIndexBuilder::title_source`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TitleSource {
+    /// The note's file name, less its extension. The same as its `name`.
+    #[default]
+    Filename,
+    /// The note's `title` front matter property, if it has one.
+    Frontmatter,
+    /// The text of the note's first `# ` heading, if it has one.
+    FirstHeading,
+}
+
+/// Builds a [`VaultIndex`] with more control over indexing than
+/// [`VaultIndex::new`] offers.
+///
+/// ```rust
+/// use vault_dweller::VaultIndexBuilder;
+///
+/// let vi = VaultIndexBuilder::new(None)
+///     .include_obsidian_folder(false)
+///     .max_depth(Some(2))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct VaultIndexBuilder {
+    path_to_vault: Option<PathBuf>,
+    include_obsidian_folder: bool,
+    max_depth: Option<u32>,
+    progress_callback: Option<ProgressCallback>,
+    strict: bool,
+    source: Option<SharedSource>,
+    parse_options: ParseOptions,
+    note_extensions: Vec<String>,
+    notes_only: bool,
+    content_cache: Option<CacheCapacity>,
+    resolve_links_eagerly: bool,
+}
+
+/// A callback run by [`VaultIndexBuilder`] for every file it indexes.
+#[derive(Clone)]
+struct ProgressCallback(Arc<dyn Fn(&Path) + Send + Sync>);
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// A custom parser for front matter strings, set with
+/// [`VaultIndexBuilder::property_parser`].
+#[derive(Clone)]
+struct PropertyParser(Arc<PropertyParserFn>);
+type PropertyParserFn = dyn Fn(&str) -> Option<Property> + Send + Sync;
+impl fmt::Debug for PropertyParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PropertyParser")
+    }
+}
+
+/// The builder options that change how a note's front matter is parsed.
+/// Each note keeps a copy, so that it's parsed the same way when read
+/// again.
+#[derive(Debug, Clone)]
+struct ParseOptions {
+    property_parser: Option<PropertyParser>,
+    normalize_property_keys: bool,
+    normalize_line_endings: bool,
+    title_source: TitleSource,
+}
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            property_parser: None,
+            normalize_property_keys: false,
+            normalize_line_endings: true,
+            title_source: TitleSource::Filename,
+        }
+    }
+}
+impl ParseOptions {
+    /// Prepares a note's contents for parsing, turning `\r\n` line endings
+    /// into `\n` unless told not to.
+    fn normalize<'a>(&self, contents: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+        let contents = contents.into();
+        if self.normalize_line_endings && contents.contains('\r') {
+            Cow::Owned(contents.replace("\r\n", "\n"))
+        } else {
+            contents
+        }
+    }
+}
+
+/// The extension of notes, unless [`VaultIndexBuilder::note_extensions`]
+/// says otherwise.
+const DEFAULT_NOTE_EXTENSION: &str = "md";
+
+/// Finds the host part of a URL, without any user info or port.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    host.split(':').next().unwrap_or_default()
+}
+
+/// Whether a path has one of the given extensions, ignoring case.
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Normalized front matter keys mapped to the keys as written.
+type RawKeys = HashMap<String, String>;
+
+impl VaultIndexBuilder {
+    /// Creates a new builder for the vault at the given path. As with
+    /// [`VaultIndex::new`], `None` builds an empty index.
+    pub fn new(path_to_vault: Option<&str>) -> Self {
+        Self {
+            path_to_vault: path_to_vault.map(PathBuf::from),
+            include_obsidian_folder: true,
+            max_depth: None,
+            progress_callback: None,
+            strict: false,
+            source: None,
+            parse_options: Default::default(),
+            note_extensions: vec![DEFAULT_NOTE_EXTENSION.to_string()],
+            notes_only: false,
+            content_cache: None,
+            resolve_links_eagerly: false,
+        }
+    }
+
+    /// Whether the `.obsidian` config folder should be indexed. Defaults
+    /// to `true`.
+    pub fn include_obsidian_folder(mut self, include_obsidian_folder: bool) -> Self {
+        self.include_obsidian_folder = include_obsidian_folder;
+        self
+    }
+
+    /// Whether to leave out everything but notes and folders, for speed
+    /// when attachments aren't needed. Folders are still walked for the
+    /// notes in them, but `files` is left empty, and files don't appear
+    /// in the tree either. Defaults to `false`.
+    pub fn notes_only(mut self, notes_only: bool) -> Self {
+        self.notes_only = notes_only;
+        self
+    }
+
+    /// Keeps the contents of notes that the index reads in memory, up to
+    /// `capacity`, dropping the least recently read first. Defaults to `None`.
+    pub fn content_cache(mut self, capacity: Option<CacheCapacity>) -> Self {
+        self.content_cache = capacity;
+        self
+    }
+
+    /// Whether to resolve every note's links while indexing, filling in
+    /// [`NoteItem::resolved_links`]. Defaults to `false`.
+    pub fn resolve_links_eagerly(mut self, resolve_links_eagerly: bool) -> Self {
+        self.resolve_links_eagerly = resolve_links_eagerly;
+        self
+    }
+
+    /// Limits how deep into the vault's folders indexing goes. Items at
+    /// the root of the vault have a depth of 1. Folders at the cutoff
+    /// are still indexed, but their contents are not, so `Some(0)`
+    /// indexes nothing at all. Defaults to `None` (no limit).
+    pub fn max_depth(mut self, max_depth: Option<u32>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets a parser for front matter string values, for types that YAML
+    /// has no notion of (such as durations like `1h30m`). It's tried on
+    /// every string value, including those inside lists and maps; when it
+    /// returns `None`, the value is kept as a [`Property::Text`].
+    pub fn property_parser(mut self, parser: impl Fn(&str) -> Option<Property> + Send + Sync + 'static) -> Self {
+        self.parse_options.property_parser = Some(PropertyParser(Arc::new(parser)));
+        self
+    }
+
+    /// Whether front matter keys should be trimmed and lowercased, so that
+    /// `Status` and `status ` are both indexed as `status`. Where keys
+    /// differing only in case are both set, the last one wins. The key as
+    /// it was written is kept in [`NoteItem::raw_key`]. Defaults to `false`.
+    pub fn normalize_property_keys(mut self, normalize_property_keys: bool) -> Self {
+        self.parse_options.normalize_property_keys = normalize_property_keys;
+        self
+    }
+
+    /// Whether Windows (`\r\n`) line endings should be read as plain `\n`
+    /// when parsing notes, so that line-based parsing of headings, blocks
+    /// and front matter works the same either way. Only what's parsed is
+    /// affected; notes are never rewritten. Defaults to `true`.
+    pub fn normalize_line_endings(mut self, normalize_line_endings: bool) -> Self {
+        self.parse_options.normalize_line_endings = normalize_line_endings;
+        self
+    }
+
+    /// Where each note's [`title`](NoteItem::title) should come from.
+    /// Notes without a `title` property or a heading, as asked for, fall
+    /// back to their file name. Defaults to [`TitleSource::Filename`].
+    pub fn title_source(mut self, title_source: TitleSource) -> Self {
+        self.parse_options.title_source = title_source;
+        self
+    }
+
+    /// Sets a callback to run for every note and file indexed, with the
+    /// path of the file on the disk. Handy for showing progress while
+    /// indexing a large vault.
+    pub fn progress_callback(mut self, callback: impl Fn(&Path) + Send + Sync + 'static) -> Self {
+        self.progress_callback = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Whether a malformed note should make [`VaultIndexBuilder::build`]
+    /// return an `Err` rather than a warning. Defaults to `false`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Reads the vault from the given [`VaultSource`] instead of the disk.
+    /// Notes indexed this way read their contents from the source too.
+    /// Defaults to [`StdFs`].
+    pub fn source(mut self, source: impl VaultSource + 'static) -> Self {
+        self.source = Some(SharedSource(Arc::new(source)));
+        self
+    }
+
+    fn vault_source(&self) -> &dyn VaultSource {
+        match &self.source {
+            Some(source) => source.0.as_ref(),
+            None => &StdFs,
+        }
+    }
+
+    /// Sets which file extensions mark a file as a note rather than an
+    /// attachment. Extensions are matched ignoring case, so `.MD` files are
+    /// notes too. Defaults to just `md`.
+    pub fn note_extensions(mut self, extensions: &[&str]) -> Self {
+        self.note_extensions = extensions.iter().map(|ext| ext.trim_start_matches('.').to_string()).collect();
+        self
+    }
+
+    /// Whether a file is a note, going by its extension.
+    fn is_note(&self, path: &Path) -> bool {
+        has_extension(path, &self.note_extensions)
+    }
+
+    fn report_progress(&self, path: &Path) {
+        if let Some(callback) = &self.progress_callback {
+            (callback.0)(path);
+        }
+    }
+
+    /// Fails on the first of a note's parse warnings in strict mode, and
+    /// otherwise adds them to the index's warnings.
+    fn handle_warnings(&self, note_warnings: Vec<IndexWarning>, warnings: &mut Vec<IndexWarning>) -> Result<(), io::Error> {
+        if self.strict {
+            if let Some(warning) = note_warnings.into_iter().next() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, warning.message));
+            }
+        } else {
+            warnings.extend(note_warnings);
+        }
+        Ok(())
+    }
+
+    /// Builds the [`VaultIndex`].
+    pub fn build(self) -> Result<VaultIndex, io::Error> {
+        let mut index = VaultIndex::from_builder(&self)?;
+        index.content_cache = Mutex::new(ContentCache::new(self.content_cache));
+        if self.resolve_links_eagerly {
+            index.resolve_all_links();
+        }
+        Ok(index)
+    }
+}
+
+/// Options for [`NoteItem::properties_as_json_with`]. The defaults give
+/// the same JSON as [`NoteItem::properties_as_json`].
+#[derive(Debug, Clone, Default)]
+pub struct PropertyJsonOpts {
+    /// When set, lists are written as a single string, with their items
+    /// joined by this separator (such as `", "`), rather than as arrays.
+    /// Lists holding other lists or maps are still written as arrays.
+    pub list_separator: Option<String>,
+    /// When set, dates are written as strings in this `strftime` format
+    /// (such as `"%Y-%m-%d"`), rather than as Unix timestamps. An invalid
+    /// format falls back to RFC 3339.
+    pub date_format: Option<String>,
+}
+
+/// A note's contents, split into its parsed front matter and its body.
+/// Returned by [`NoteItem::read`].
+#[derive(Debug)]
+pub struct ParsedNote {
+    pub frontmatter: IndexMap<String, Property>,
+    pub body: String,
+}
+
+/// Counts of what's in a vault. Returned by [`VaultIndex::stats`].
+///
+/// `total_words` is only filled in by [`VaultIndex::stats_with_words`],
+/// since counting words means reading every note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultStats {
+    pub notes: usize,
+    pub files: usize,
+    pub folders: usize,
+    pub tags: usize,
+    pub total_words: Option<usize>,
+}
+
+/// A match found by [`VaultIndex::search_regex`]. `line` and `column`
+/// count from 0, and `captures` holds the whole match and then each group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub note: String,
+    pub line: usize,
+    pub column: usize,
+    pub captures: Vec<Option<String>>,
+}
+
+/// A note readied for a full-text search engine. Returned by
+/// [`VaultIndex::export_documents`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchDoc {
+    pub name: String,
+    #[serde(serialize_with = "serialize_path")]
+    pub path: PathBuf,
+    pub tags: Vec<String>,
+    pub body_plaintext: String,
+}
+
+/// How two notes' properties differ. Returned by
+/// [`VaultIndex::property_diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertyDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub changed: Vec<(String, Property, Property)>,
+}
+impl PropertyDiff {
+    /// Whether the two notes have exactly the same properties.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Problems found across a vault. Returned by
+/// [`VaultIndex::health_check`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthReport {
+    pub broken_links: Vec<(String, String)>,
+    pub broken_embeds: Vec<(String, String)>,
+    pub orphan_notes: Vec<String>,
+    pub duplicate_names: Vec<(String, Vec<PathBuf>)>,
+    pub frontmatter_warnings: Vec<IndexWarning>,
+}
+impl HealthReport {
+    /// Counts the problems in every category.
+    pub fn problem_count(&self) -> usize {
+        self.broken_links.len()
+            + self.broken_embeds.len()
+            + self.orphan_notes.len()
+            + self.duplicate_names.len()
+            + self.frontmatter_warnings.len()
+    }
+    /// Whether no problems were found at all.
+    pub fn is_healthy(&self) -> bool {
+        self.problem_count() == 0
+    }
+}
+
+/// What an [`IndexWarning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningKind {
+    /// Front matter that isn't valid YAML.
+    MalformedFrontmatter,
+    /// Front matter opened with `---` but never closed.
+    UnclosedFrontmatter,
+    /// Front matter indented with tabs, which YAML doesn't allow.
+    TabIndentedFrontmatter,
+    /// A front matter key set more than once, of which the last value is
+    /// used.
+    DuplicateKey,
+}
+
+/// A problem found while indexing that didn't stop the build. See
+/// [`VaultIndex::warnings`]. `message` describes the problem, naming the
+/// note it was found in, and `local_path` is that note's local path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexWarning {
+    pub kind: WarningKind,
+    pub local_path: PathBuf,
+    pub message: String,
+}
+impl fmt::Display for IndexWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
     }
 }
 
 /// Represents everything in a vault.
+///
+/// `notes` is keyed by note name, or by local path for notes sharing a
+/// name with another; see [`VaultIndex::duplicate_names`].
 #[derive(Debug)]
 pub struct VaultIndex {
     pub name: String,
@@ -237,6 +1194,13 @@ pub struct VaultIndex {
     pub tags: IndexMap<String, Vec<String>>,
     pub properties: Vec<String>,
     pub tree: Tree,
+    /// Problems found while indexing that didn't stop the build, such as
+    /// notes with malformed front matter, each tagged with its
+    /// [`WarningKind`]. See [`VaultIndexBuilder::strict`].
+    pub warnings: Vec<IndexWarning>,
+    virtual_contents: HashMap<PathBuf, String>,
+    content_cache: Mutex<ContentCache>,
+    resolve_links_eagerly: bool,
 }
 
 impl VaultIndex {
@@ -244,89 +1208,189 @@ impl VaultIndex {
     /// The path is wrapped in an `Option`, and you may supply `None` if you
     /// do not want to generate a [`VaultIndex`] from an existing vault.
     pub fn new(path_to_vault: Option<&str>, include_obsidian_folder: bool) -> Result<Self, io::Error> {
+        VaultIndexBuilder::new(path_to_vault)
+            .include_obsidian_folder(include_obsidian_folder)
+            .build()
+    }
+
+    /// Returns a [`VaultIndexBuilder`] for the vault at the given path.
+    pub fn builder(path_to_vault: Option<&str>) -> VaultIndexBuilder {
+        VaultIndexBuilder::new(path_to_vault)
+    }
+
+    /// Creates a [`VaultIndex`] of just the given files (and the folders
+    /// leading to them) in the vault at `root`.
+    pub fn from_paths(root: &Path, paths: &[PathBuf]) -> Result<Self, io::Error> {
+        let options = VaultIndexBuilder::new(root.to_str());
+        let source = options.vault_source();
+        if !source.metadata(root).is_ok_and(|m| m.is_dir) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "The path specified either could not be found, could not be accessed, or was not a directory."));
+        }
+        let vault_path = root.to_path_buf();
+        let name = root.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_owned();
+        let mut tree: Tree = Tree::new();
+        tree.arena[0].name = name.clone();
+        let mut warnings: Vec<IndexWarning> = vec![];
+        let mut file_collection: Vec<FileFolder> = vec![];
+
+        let mut local_paths: Vec<PathBuf> = paths.iter()
+            .map(|path| {
+                let path = if path.starts_with(root) { path.clone() } else { root.join(path) };
+                path.strip_prefix(root)
+                    .map(Path::to_path_buf)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("{} isn't in the vault!", path.display())))
+            })
+            .collect::<Result<_, _>>()?;
+        // Sorted by component, so that the tree comes out in the order
+        // `VaultIndex::new` walks the vault in.
+        local_paths.sort();
+        local_paths.dedup();
+
+        let mut folder_nodes: HashMap<PathBuf, usize> = HashMap::new();
+        for local_path in local_paths {
+            let path = vault_path.join(&local_path);
+            let is_dir = source.metadata(&path)?.is_dir;
+            let folder = if is_dir { Some(local_path.as_path()) } else { local_path.parent() };
+            let mut parent = 0;
+            let mut folder_path = PathBuf::new();
+            for component in folder.into_iter().flat_map(Path::components) {
+                folder_path.push(component);
+                parent = match folder_nodes.get(&folder_path) {
+                    Some(idx) => *idx,
+                    None => {
+                        file_collection.push(Self::generate_folder_item(&vault_path.join(&folder_path), &vault_path)?);
+                        let folder_name = component.as_os_str().to_str().unwrap().to_owned();
+                        let idx = tree.add_child(parent, folder_name, ItemType::Folder).expect("Couldn't find parent in tree!");
+                        folder_nodes.insert(folder_path.clone(), idx);
+                        idx
+                    },
+                };
+            }
+            if is_dir {
+                continue;
+            }
+            if options.is_note(&path) {
+                tree.add_child(parent, path.file_stem().unwrap().to_str().unwrap().to_owned(), ItemType::Note);
+                let (note, note_warnings) = Self::generate_note_item(&path, &vault_path, &options)?;
+                options.handle_warnings(note_warnings, &mut warnings)?;
+                file_collection.push(note);
+            } else {
+                tree.add_child(parent, path.file_name().unwrap().to_str().unwrap().to_owned(), ItemType::File);
+                file_collection.push(Self::generate_file_item(&path, &vault_path, None)?);
+            }
+        }
+
+        Ok(Self::from_filefolders(name, vault_path, file_collection, tree, warnings))
+    }
+
+    fn from_builder(options: &VaultIndexBuilder) -> Result<Self, io::Error> {
         let mut name: String = Default::default();
         let mut path: PathBuf = PathBuf::new();
-        let mut notes: IndexMap<String, NoteItem> = IndexMap::new();
-        let mut files: IndexMap<String, FileItem> = IndexMap::new();
-        let mut folders: Vec<FolderItem> = vec![];
-        let mut filepath_ref: IndexMap<String, String> = IndexMap::new();
-        let mut tags: IndexMap<String, Vec<String>> = Default::default();
-        let mut properties: Vec<String> = vec![];
+        let mut file_collection: Vec<FileFolder> = vec![];
         let mut tree: Tree = Tree::new();
-        if let Some(vault_path) = path_to_vault {
-            let p = PathBuf::from(vault_path);
+        let mut warnings: Vec<IndexWarning> = vec![];
+        if let Some(vault_path) = &options.path_to_vault {
+            let p = vault_path.clone();
             name = p.file_name().unwrap().to_str().unwrap().to_owned();
             tree.arena[0].name = name.clone();
             path = p.clone();
-            if !p.is_dir() {
+            if !options.vault_source().metadata(&p).is_ok_and(|m| m.is_dir) {
                 return Err(io::Error::new(io::ErrorKind::NotFound, "The path specified either could not be found, could not be accessed, or was not a directory."));
             }
 
-            let file_collection = Self::recursive_generate_filefolders(&p, &p, include_obsidian_folder, &mut tree, 0);
-            
-            for file in file_collection {
-                match file {
-                    FileFolder::Note(fi) => {
-                        filepath_ref.insert(fi.local_path.to_str().unwrap().to_string().clone(), fi.name.clone());
-                        for tag in &fi.tags {
-                            if let Some(tag_list) = tags.get_mut(tag) {
-                                tag_list.push(fi.name.clone());
-                            } else {
-                                tags.insert(tag.clone(), vec![fi.name.clone()]);
-                            }
-                        }
-                        for key in fi.properties.keys() {
-                            properties.push(key.clone());
-                        }
-                        notes.insert(fi.name.clone(), fi);
-                    }
-                    FileFolder::File(fi) => {
-                        filepath_ref.insert(fi.local_path.to_str().unwrap().to_string().clone(), fi.name.clone());
-                        files.insert(fi.name.clone(), fi); 
-                    },
-                    FileFolder::Folder(fi) => {
-                        folders.push(fi);
-                    },
-                }
-            }
-            
-            /*
-            println!("\n==== NAME ====");
-            println!("{:?}", name);
-            */
-            /*
-            println!("\n==== NOTES ====");
-            println!("{:?}", notes);
-            */
-            /*
-            println!("\n==== FILES ====");
-            println!("{:?}", files);
-            */
-            /*
-            println!("\n==== FILE PATHS ====");
-            println!("{:?}", filepath_ref);
-            */
-            /*
-            println!("\n==== FOLDERS ====");
-            println!("{:?}", folders);
-            */
-            /*
-            println!("\n==== TAGS ====");
-            println!("{:?}", tags);
-            */
-            /*
-            println!("\n==== PROPERTIES ====");
-            println!("{:?}", properties);
-            */
-            /*
-            println!("\n==== TREE ====");
-            println!("{:?}", tree);
-            */
+            file_collection = Self::recursive_generate_filefolders(&p, &p, options, &mut tree, 0, &mut warnings)?;
         }
 
-        properties.dedup();
+        Ok(Self::from_filefolders(name, path, file_collection, tree, warnings))
+    }
 
-        let vi = VaultIndex {
+    fn from_filefolders(name: String, path: PathBuf, file_collection: Vec<FileFolder>, tree: Tree, warnings: Vec<IndexWarning>) -> Self {
+        let mut notes: IndexMap<String, NoteItem> = IndexMap::new();
+        let mut files: IndexMap<String, FileItem> = IndexMap::new();
+        let mut folders: Vec<FolderItem> = vec![];
+        let mut filepath_ref: IndexMap<String, String> = IndexMap::new();
+        let mut tags: IndexMap<String, Vec<String>> = Default::default();
+        let mut properties: Vec<String> = vec![];
+
+        // A note at the root of the vault has a local path equal to its
+        // name, so it always gets keyed by name.
+        let root_note_names: HashSet<String> = file_collection.iter()
+            .filter_map(|file| match file {
+                FileFolder::Note(fi) if fi.local_path.components().count() == 1 => Some(fi.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        for file in file_collection {
+            match file {
+                FileFolder::Note(fi) => {
+                    // A note that shares its name with one already indexed
+                    // is keyed by its local path.
+                    let is_root_note = fi.local_path.components().count() == 1;
+                    let key = if !is_root_note && (notes.contains_key(&fi.name) || root_note_names.contains(&fi.name)) {
+                        fi.local_path.to_str().unwrap().to_string()
+                    } else {
+                        fi.name.clone()
+                    };
+                    filepath_ref.insert(fi.local_path.to_str().unwrap().to_string().clone(), key.clone());
+                    for tag in &fi.tags {
+                        if let Some(tag_list) = tags.get_mut(tag) {
+                            tag_list.push(key.clone());
+                        } else {
+                            tags.insert(tag.clone(), vec![key.clone()]);
+                        }
+                    }
+                    for key in fi.properties.keys() {
+                        properties.push(key.clone());
+                    }
+                    notes.insert(key, *fi);
+                }
+                FileFolder::File(fi) => {
+                    filepath_ref.insert(fi.local_path.to_str().unwrap().to_string().clone(), fi.name.clone());
+                    files.insert(fi.name.clone(), fi); 
+                },
+                FileFolder::Folder(fi) => {
+                    folders.push(fi);
+                },
+            }
+        }
+        
+        /*
+        println!("\n==== NAME ====");
+        println!("{:?}", name);
+        */
+        /*
+        println!("\n==== NOTES ====");
+        println!("{:?}", notes);
+        */
+        /*
+        println!("\n==== FILES ====");
+        println!("{:?}", files);
+        */
+        /*
+        println!("\n==== FILE PATHS ====");
+        println!("{:?}", filepath_ref);
+        */
+        /*
+        println!("\n==== FOLDERS ====");
+        println!("{:?}", folders);
+        */
+        /*
+        println!("\n==== TAGS ====");
+        println!("{:?}", tags);
+        */
+        /*
+        println!("\n==== PROPERTIES ====");
+        println!("{:?}", properties);
+        */
+        /*
+        println!("\n==== TREE ====");
+        println!("{:?}", tree);
+        */
+
+        properties.dedup();
+
+        VaultIndex {
             name,
             path: Some(path),
             notes,
@@ -336,14 +1400,16 @@ impl VaultIndex {
             tags,
             properties,
             tree,
-        };
-
-        Ok(vi)
+            warnings,
+            virtual_contents: HashMap::new(),
+            content_cache: Default::default(),
+            resolve_links_eagerly: false,
+        }
     }
 
     /// Retrieves a [`NoteItem`] from the [`VaultIndex`] by name or local
     /// path. Returns `None` if there was no file matching that name/path
-    /// in the index.
+    /// in the index. Files can be given with or without their extension.
     ///
     /// ```rust
     /// use vault_dweller::VaultIndex;
@@ -358,13 +1424,16 @@ impl VaultIndex {
     /// let fb = vi.get_item("Folder A/Lorem Ipsum");
     /// ```
     pub fn get_item(&self, local_path: &str) -> Option<VaultItem> {
-        let mut adj_local_path: &str = &local_path.replace("/", "\\");
-        match adj_local_path.find("\\") {
+        let mut adj_local_path: &str = &local_path.replace(['/', '\\'], MAIN_SEPARATOR_STR);
+        match adj_local_path.find(MAIN_SEPARATOR) {
             Some(_) =>  {
                 if let Some(p) = self.filepath_ref.get(adj_local_path) {
                     adj_local_path = &p;
                 } else {
-                    return None;
+                    let stem_path = Path::new(adj_local_path);
+                    return self.files.values()
+                        .find(|f| f.local_path.with_extension("") == stem_path)
+                        .map(VaultItem::File);
                 }
             },
             None => {
@@ -376,26 +1445,229 @@ impl VaultIndex {
         } else if let Some(file) = self.files.get(adj_local_path) {
             return Some(VaultItem::File(file));
         } else {
-            return None;
+            return self.files.values()
+                .find(|f| Path::new(&f.name).file_stem().is_some_and(|stem| stem == adj_local_path))
+                .map(VaultItem::File);
+        }
+    }
+
+    /// Returns what kind of item is at the given local path (or, for
+    /// notes and files, name) without fetching it. Returns `None` if
+    /// nothing in the vault matches.
+    pub fn item_type(&self, local_path: &str) -> Option<ItemType> {
+        let adj_local_path = local_path.replace(['/', '\\'], MAIN_SEPARATOR_STR);
+        if let Some(item) = self.get_item(local_path) {
+            match item {
+                VaultItem::Note(_) => Some(ItemType::Note),
+                VaultItem::File(_) => Some(ItemType::File),
+            }
+        } else if self.folders.iter().any(|f| f.local_path == Path::new(&adj_local_path)) {
+            Some(ItemType::Folder)
+        } else {
+            None
+        }
+    }
+
+    /// Looks up several items at once with [`VaultIndex::get_item`],
+    /// returning a map from each path given to the item found, or `None`
+    /// for paths that didn't match anything.
+    pub fn get_items<'a>(&'a self, paths: &[&str]) -> HashMap<String, Option<VaultItem<'a>>> {
+        paths.iter()
+            .map(|path| (path.to_string(), self.get_item(path)))
+            .collect()
+    }
+
+    /// Retrieves a [`VaultItem`] from the [`VaultIndex`] by its absolute
+    /// path on the disk, such as one returned by a file picker. Returns
+    /// `None` if the path is outside the vault or doesn't match an item.
+    pub fn get_item_by_abs_path(&self, path: &Path) -> Option<VaultItem<'_>> {
+        let vault_path = self.path.as_ref()?;
+        let local_path = path.strip_prefix(vault_path).ok()?;
+        // Notes can have any of the configured extensions, in any case.
+        if let Some(note) = self.notes.values().find(|note| note.path == path) {
+            return Some(VaultItem::Note(note));
         }
+        self.get_item(local_path.to_str()?)
     }
 
     pub fn get_note(&self, local_path: &str) -> Option<&NoteItem> {
-        let mut adj_local_path: &str = &local_path.replace("/", "\\");
-        match adj_local_path.find("\\") {
-            Some(_) =>  {
-                if let Some(p) = self.filepath_ref.get(adj_local_path) {
-                    adj_local_path = &p;
-                } else {
-                    return None;
+        self.notes.get(&self.note_key(local_path)?)
+    }
+
+    /// Works out the key in `notes` for a note name or local path.
+    fn note_key(&self, local_path: &str) -> Option<String> {
+        let adj_local_path = local_path.replace(['/', '\\'], MAIN_SEPARATOR_STR);
+        let key = match adj_local_path.find(MAIN_SEPARATOR) {
+            Some(_) => self.filepath_ref.get(&adj_local_path)?.clone(),
+            None => local_path.to_string(),
+        };
+        if self.notes.contains_key(&key) {
+            Some(key)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the keys of the notes whose files have been modified or
+    /// deleted since they were indexed.
+    pub fn stale_notes(&self) -> Result<Vec<String>, io::Error> {
+        let mut stale: Vec<String> = vec![];
+        for (key, note) in &self.notes {
+            if note.archive.is_some() {
+                continue;
+            }
+            let modified = match &note.source {
+                Some(source) => source.0.metadata(&note.path).map(|m| m.modified),
+                None => fs::metadata(&note.path).map(|m| m.modified().ok()),
+            };
+            match modified {
+                Ok(modified) if modified == note.modified => {},
+                Ok(_) => stale.push(key.clone()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => stale.push(key.clone()),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(stale)
+    }
+
+    /// Re-reads a note and updates its entry in the index to match.
+    pub fn reindex_note(&mut self, name: &str) -> Result<(), io::Error> {
+        let Some(key) = self.note_key(name) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't match note name or local path!"));
+        };
+        let old_note = &self.notes[&key];
+        self.lock_content_cache().remove(&old_note.local_path);
+        let contents = self.contents_of(old_note)?;
+        let modified: Option<SystemTime> = match &old_note.source {
+            Some(source) => source.0.metadata(&old_note.path).ok().and_then(|m| m.modified),
+            None => fs::metadata(&old_note.path).and_then(|m| m.modified()).ok(),
+        };
+        let (mut note, warnings) = Self::parse_note_item(&old_note.path, old_note.local_path.clone(), &contents, modified, &old_note.parse_options);
+        note.archive = old_note.archive.clone();
+        note.source = old_note.source.clone();
+        if self.resolve_links_eagerly {
+            note.resolved_links = self.resolve_links(&note);
+        }
+        // The note's earlier warnings are replaced, not added to.
+        self.warnings.retain(|warning| warning.local_path != note.local_path);
+        self.warnings.extend(warnings);
+
+        for tag in &old_note.tags {
+            if let Some(tag_list) = self.tags.get_mut(tag) {
+                tag_list.retain(|k| k != &key);
+                if tag_list.is_empty() {
+                    self.tags.shift_remove(tag);
                 }
+            }
+        }
+        for tag in &note.tags {
+            self.tags.entry(tag.clone()).or_default().push(key.clone());
+        }
+        for property in note.properties.keys() {
+            if !self.properties.contains(property) {
+                self.properties.push(property.clone());
+            }
+        }
+        self.notes.insert(key, note);
+        Ok(())
+    }
+
+    /// Merges `patch` into a note's front matter, rewriting only the
+    /// patched keys, then re-indexes the note.
+    pub fn patch_frontmatter(&mut self, name: &str, patch: HashMap<String, Property>) -> Result<(), io::Error> {
+        let Some(note) = self.get_note(name) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't match note name or local path!"));
+        };
+        #[cfg(feature = "zip")]
+        if note.archive.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Notes in a zip archive can't be written to!"));
+        }
+        if note.source.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Notes from a custom VaultSource can't be written to!"));
+        }
+        let is_virtual = self.virtual_contents.contains_key(&note.local_path);
+        let raw_contents = match self.virtual_contents.get(&note.local_path) {
+            Some(contents) => contents.clone(),
+            None => note.get_contents()?,
+        };
+        let is_crlf = raw_contents.contains("\r\n");
+        let contents = raw_contents.replace("\r\n", "\n");
+        let (frontmatter, _) = markdown::split_frontmatter(&contents);
+        if let Some(yaml) = frontmatter.filter(|yaml| !yaml.trim().is_empty()) {
+            if let Some(tab_error) = Self::tab_indentation_error(yaml) {
+                return Err(tab_error);
+            }
+            let docs = YamlLoader::load_from_str(yaml)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Error parsing yaml! {}", e)))?;
+            if !matches!(docs.first(), Some(Yaml::Hash(_))) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "The note's front matter isn't a map of properties!"));
+            }
+        }
+
+        let normalize_keys = note.parse_options.normalize_property_keys;
+        let same_key = |a: &str, b: &str| if normalize_keys {
+            a.trim().to_lowercase() == b.trim().to_lowercase()
+        } else {
+            a == b
+        };
+        let mut yaml = frontmatter.unwrap_or_default().to_string();
+        if !yaml.is_empty() && !yaml.ends_with('\n') {
+            yaml.push('\n');
+        }
+        let mut patch_keys: Vec<&String> = patch.keys().collect();
+        patch_keys.sort();
+        for key in patch_keys {
+            // The last of any duplicate keys is the one that's indexed.
+            let existing = markdown::frontmatter_keys(&yaml).into_iter().rev().find(|(k, _)| same_key(k, key));
+            match existing {
+                Some((raw_key, range)) => yaml.replace_range(range, &Self::yaml_entry(&raw_key, &patch[key])?),
+                None => yaml.push_str(&Self::yaml_entry(key, &patch[key])?),
+            }
+        }
+
+        let mut patched = match frontmatter {
+            Some(old_yaml) => {
+                let yaml_start = old_yaml.as_ptr() as usize - contents.as_ptr() as usize;
+                format!("{}{}{}", &contents[..yaml_start], yaml, &contents[yaml_start + old_yaml.len()..])
             },
-            None => {
-                adj_local_path = local_path;
-            },
+            None => format!("---\n{}---\n{}", yaml, contents),
         };
+        if is_crlf {
+            patched = patched.replace('\n', "\r\n");
+        }
+        if is_virtual {
+            let local_path = note.local_path.clone();
+            self.virtual_contents.insert(local_path, patched);
+        } else {
+            fs::write(&note.path, patched)?;
+        }
+
+        self.reindex_note(name)
+    }
+
+    /// Writes a single front matter entry as YAML, ending in a line break.
+    fn yaml_entry(key: &str, value: &Property) -> Result<String, io::Error> {
+        let mut yaml_hash = YamlHash::new();
+        yaml_hash.insert(Yaml::String(key.to_string()), value.to_yaml());
+        let mut yaml_out = String::new();
+        YamlEmitter::new(&mut yaml_out)
+            .dump(&Yaml::Hash(yaml_hash))
+            .map_err(|e| io::Error::other(format!("Error writing yaml! {:?}", e)))?;
+        // The emitter starts its output with the opening `---`.
+        Ok(format!("{}\n", yaml_out.trim_start_matches("---").trim_start_matches('\n')))
+    }
+
+    /// Returns the position of the named note in the `notes` map. This is
+    /// stable for as long as the index isn't rebuilt, so it can be used as
+    /// a numeric id for the note.
+    pub fn note_index(&self, name: &str) -> Option<usize> {
+        self.notes.get_index_of(name)
+    }
 
-        self.notes.get(adj_local_path)
+    /// Retrieves a note by its position in the `notes` map. The inverse of
+    /// [`VaultIndex::note_index`].
+    pub fn note_at(&self, idx: usize) -> Option<&NoteItem> {
+        self.notes.get_index(idx).map(|(_, note)| note)
     }
 
     /// Retrieves a note's contents by name or local path as a String. 
@@ -416,7 +1688,7 @@ impl VaultIndex {
     pub fn get_note_contents(&self, local_path: &str) -> Result<String, io::Error> {
         if let Some(entry) = self.get_item(local_path) {
             return match entry {
-                VaultItem::Note(n) => n.get_contents(),
+                VaultItem::Note(n) => self.contents_of(n),
                 _ => Err(io::Error::new(io::ErrorKind::Other, "Couldn't match local path!")),
             }
             
@@ -425,42 +1697,867 @@ impl VaultIndex {
         }  
     }
 
+    /// Sets in-memory contents for a note, to be read instead of those on
+    /// the disk. Call [`VaultIndex::reindex_note`] to re-index from them.
+    pub fn set_virtual_contents(&mut self, name: &str, contents: String) {
+        if let Some(note) = self.get_note(name) {
+            let local_path = note.local_path.clone();
+            self.virtual_contents.insert(local_path, contents);
+        }
+    }
+
+    /// Removes a note's in-memory contents set with
+    /// [`VaultIndex::set_virtual_contents`].
+    pub fn clear_virtual_contents(&mut self, name: &str) {
+        if let Some(note) = self.get_note(name) {
+            let local_path = note.local_path.clone();
+            self.virtual_contents.remove(&local_path);
+        }
+    }
+
+    /// Gets a note's contents, preferring any virtual contents over the
+    /// content cache and the disk.
+    fn contents_of(&self, note: &NoteItem) -> Result<String, io::Error> {
+        let contents = match self.virtual_contents.get(&note.local_path) {
+            Some(contents) => contents.clone(),
+            None => {
+                let cached = self.lock_content_cache().get(&note.local_path);
+                match cached {
+                    Some(contents) => contents,
+                    None => {
+                        let contents = note.get_contents()?;
+                        self.lock_content_cache().insert(&note.local_path, contents.clone());
+                        contents
+                    },
+                }
+            },
+        };
+        Ok(note.parse_options.normalize(contents).into_owned())
+    }
+
+    /// Returns how the index's cache of note contents has been used so
+    /// far. See [`VaultIndexBuilder::content_cache`]. Everything is zero
+    /// if there's no cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.lock_content_cache().stats()
+    }
+
+    fn lock_content_cache(&self) -> std::sync::MutexGuard<'_, ContentCache> {
+        // The cache is never left half-updated, so a panic elsewhere while
+        // it was locked doesn't matter.
+        self.content_cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns every local path known to the index, sorted. Useful for
+    /// working out why a lookup by local path didn't find anything.
+    pub fn local_paths(&self) -> Vec<&str> {
+        let mut paths: Vec<&str> = self.filepath_ref.keys().map(|k| k.as_str()).collect();
+        paths.sort_by(|a, b| unicode_cmp(a, b));
+        paths
+    }
+
+    /// Returns every tag in the vault (including parent tags), sorted so
+    /// that accented letters sort alongside their unaccented forms.
+    pub fn all_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.tags.keys().map(|k| k.as_str()).collect();
+        tags.sort_by(|a, b| unicode_cmp(a, b));
+        tags
+    }
+
+    /// Returns each extension found among the vault's files (not its
+    /// notes), lowercased, without duplicates and sorted. Files without
+    /// an extension are left out.
+    pub fn file_extensions(&self) -> Vec<String> {
+        let mut extensions: Vec<String> = self.files.values()
+            .filter(|file| !file.file_type.is_empty())
+            .map(|file| file.file_type.to_lowercase())
+            .collect();
+        extensions.sort();
+        extensions.dedup();
+        extensions
+    }
+
+    /// Returns the name of every note in the vault, sorted the same way
+    /// as [`VaultIndex::all_tags`].
+    pub fn all_note_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.notes.values().map(|n| n.name.as_str()).collect();
+        names.sort_by(|a, b| unicode_cmp(a, b));
+        names
+    }
+
+    /// Returns every folder in the vault, sorted by local path the same
+    /// way as [`VaultIndex::all_tags`].
+    pub fn all_folders(&self) -> Vec<&FolderItem> {
+        let mut folders: Vec<&FolderItem> = self.folders.iter().collect();
+        folders.sort_by(|a, b| unicode_cmp(&a.local_path.to_string_lossy(), &b.local_path.to_string_lossy()));
+        folders
+    }
+
+    /// Returns every note last modified between `start` and `end`
+    /// (inclusive), as of when it was indexed.
+    pub fn notes_modified_between(&self, start: SystemTime, end: SystemTime) -> Vec<&NoteItem> {
+        self.notes.values()
+            .filter(|note| note.modified.is_some_and(|m| m >= start && m <= end))
+            .collect()
+    }
+
+    /// Returns every note with a date in the given property, paired with
+    /// that date, from earliest to latest.
+    pub fn timeline(&self, date_key: &str) -> Vec<(&NoteItem, DateTime<Utc>)> {
+        let mut out_notes: Vec<(&NoteItem, DateTime<Utc>)> = self.notes.values()
+            .filter_map(|note| Some((note, note.properties.get(date_key)?.as_date()?)))
+            .collect();
+        out_notes.sort_by_key(|(_, date)| *date);
+        out_notes
+    }
+
+    /// Returns the notes anywhere under a folder, in index order. `""`
+    /// gives every note in the vault.
+    pub fn notes_in_folder(&self, folder: &str) -> Vec<&NoteItem> {
+        let folder_path = PathBuf::from(folder.trim_matches(['/', '\\']).replace(['/', '\\'], MAIN_SEPARATOR_STR));
+        self.notes.values()
+            .filter(|note| note.local_path.parent().is_some_and(|parent| parent.starts_with(&folder_path)))
+            .collect()
+    }
+
+    /// Returns every tag (including parent tags) on the notes under a
+    /// folder, as found by [`VaultIndex::notes_in_folder`], without
+    /// duplicates and sorted the same way as [`VaultIndex::all_tags`].
+    pub fn tags_in_folder(&self, folder: &str) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.notes_in_folder(folder).into_iter()
+            .flat_map(|note| note.tags.iter().map(|tag| tag.as_str()))
+            .collect();
+        tags.sort_by(|a, b| unicode_cmp(a, b));
+        tags.dedup();
+        tags
+    }
+
+    /// Returns the notes directly inside a folder, sorted by a property.
+    /// Notes without the property come last; ties are sorted by name.
+    pub fn notes_in_folder_sorted(&self, folder: &str, by: &str, ascending: bool) -> Vec<&NoteItem> {
+        let folder_path = PathBuf::from(folder.trim_matches(['/', '\\']).replace(['/', '\\'], MAIN_SEPARATOR_STR));
+        let mut out_notes: Vec<&NoteItem> = self.notes.values()
+            .filter(|note| note.local_path.parent().unwrap_or(Path::new("")) == folder_path)
+            .collect();
+        out_notes.sort_by(|a, b| {
+            let order = match (a.properties.get(by), b.properties.get(by)) {
+                (Some(Property::Number(x)), Some(Property::Number(y))) => x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal),
+                (Some(Property::Text(x)), Some(Property::Text(y))) => unicode_cmp(x, y),
+                (Some(Property::Number(_)), Some(Property::Text(_))) => std::cmp::Ordering::Less,
+                (Some(Property::Text(_)), Some(Property::Number(_))) => std::cmp::Ordering::Greater,
+                (Some(Property::Number(_) | Property::Text(_)), _) => return std::cmp::Ordering::Less,
+                (_, Some(Property::Number(_) | Property::Text(_))) => return std::cmp::Ordering::Greater,
+                _ => std::cmp::Ordering::Equal,
+            };
+            let order = if ascending { order } else { order.reverse() };
+            order.then_with(|| unicode_cmp(&a.name, &b.name))
+        });
+        out_notes
+    }
+
+    /// Returns every note that has front matter but an empty body, such
+    /// as stub notes that only exist to hold metadata. This reads every
+    /// note from the disk.
+    pub fn metadata_only_notes(&self) -> Result<Vec<&NoteItem>, io::Error> {
+        let mut out_notes: Vec<&NoteItem> = vec![];
+        for note in self.notes.values() {
+            let contents = self.contents_of(note)?;
+            let (frontmatter, body) = markdown::split_frontmatter(&contents);
+            if frontmatter.is_some() && body.trim().is_empty() {
+                out_notes.push(note);
+            }
+        }
+        Ok(out_notes)
+    }
+
+    /// Copies a folder's notes and the attachments they embed to `dest`,
+    /// so that it can be opened as a vault of its own.
+    pub fn export_subtree(&self, folder: &str, dest: &Path) -> Result<(), io::Error> {
+        let folder_path = PathBuf::from(folder.replace(['/', '\\'], MAIN_SEPARATOR_STR));
+        if !self.folders.iter().any(|f| f.local_path == folder_path) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("Couldn't find folder {:?} in the vault!", folder)));
+        }
+
+        for note in self.notes.values() {
+            let Ok(rel_path) = note.local_path.strip_prefix(&folder_path) else {
+                continue;
+            };
+            let mut note_dest = dest.join(rel_path);
+            note_dest.set_extension(&note.file_type);
+            fs::create_dir_all(note_dest.parent().unwrap())?;
+            fs::write(&note_dest, note.get_contents()?)?;
+
+            for embed in &note.embeds {
+                if let Some(VaultItem::File(file)) = self.get_item(embed) {
+                    let file_dest = match file.local_path.strip_prefix(&folder_path) {
+                        Ok(rel_path) => dest.join(rel_path),
+                        Err(_) => dest.join(&file.local_path),
+                    };
+                    fs::create_dir_all(file_dest.parent().unwrap())?;
+                    fs::copy(&file.path, &file_dest)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the body of a note with every note it embeds (`![[Note]]`)
+    /// replaced by that note's body, recursively, up to [`MAX_EMBED_DEPTH`].
+    pub fn render_note(&self, name: &str) -> Result<String, io::Error> {
+        let Some(note) = self.get_note(name) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Couldn't match note name or local path!"));
+        };
+        let embed_matcher = Regex::new(r"!\[\[([^\]|]+)(\|[^\]]*)?\]\]").expect("REGEX FAILED");
+        let mut stack: Vec<&Path> = vec![];
+        self.render_embeds(note, None, &embed_matcher, &mut stack)
+    }
+
+    fn render_embeds<'a>(&'a self, note: &'a NoteItem, heading: Option<&str>, embed_matcher: &Regex, stack: &mut Vec<&'a Path>) -> Result<String, io::Error> {
+        let contents = self.contents_of(note)?;
+        let (_, mut body) = markdown::split_frontmatter(&contents);
+        if let Some(heading) = heading {
+            body = markdown::section(body, heading).unwrap_or_default();
+        }
+        stack.push(&note.local_path);
+
+        let mut out_body = String::new();
+        let mut last_end = 0;
+        for caps in embed_matcher.captures_iter(body) {
+            let whole = caps.get(0).unwrap();
+            let (target, fragment) = match caps[1].split_once('#') {
+                Some((target, fragment)) => (target.trim(), Some(fragment.trim())),
+                None => (caps[1].trim(), None),
+            };
+            let embedded = match self.get_note(target) {
+                Some(n) if !fragment.is_some_and(|f| f.starts_with('^'))
+                    && !stack.contains(&n.local_path.as_path())
+                    && stack.len() <= MAX_EMBED_DEPTH => Some(n),
+                _ => None,
+            };
+            out_body.push_str(&body[last_end..whole.start()]);
+            if let Some(block_id) = fragment.filter(|f| f.starts_with('^')) {
+                match self.resolve_block(target, block_id) {
+                    Some(block) => out_body.push_str(&block),
+                    None => out_body.push_str(whole.as_str()),
+                }
+                last_end = whole.end();
+                continue;
+            }
+            match embedded {
+                Some(n) => out_body.push_str(self.render_embeds(n, fragment, embed_matcher, stack)?.trim_end()),
+                None => out_body.push_str(whole.as_str()),
+            }
+            last_end = whole.end();
+        }
+        out_body.push_str(&body[last_end..]);
+
+        stack.pop();
+        Ok(out_body)
+    }
+
+    /// Sums the word counts of the notes directly inside each folder,
+    /// keyed by the folder's local path.
+    pub fn word_count_by_folder(&self) -> IndexMap<PathBuf, usize> {
+        let mut counts: IndexMap<PathBuf, usize> = IndexMap::new();
+        for note in self.notes.values() {
+            let folder = note.local_path.parent().map(Path::to_path_buf).unwrap_or_default();
+            *counts.entry(folder).or_default() += note.word_count().unwrap_or(0);
+        }
+        counts
+    }
+
+    /// Finds the note a link or embed target points to, by name or local
+    /// path, with or without the `.md` extension.
+    fn link_target(&self, target: &str) -> Option<&NoteItem> {
+        self.get_note(target.strip_suffix(".md").unwrap_or(target))
+    }
+
+    /// Finds the key of the note each of a note's links points to, as
+    /// [`VaultIndex::link_target`] would.
+    fn resolve_links(&self, note: &NoteItem) -> Vec<Option<String>> {
+        note.links.iter()
+            .map(|link| self.note_key(link.strip_suffix(".md").unwrap_or(link)))
+            .collect()
+    }
+
+    /// Fills in every note's `resolved_links`, and keeps them up to date
+    /// when notes are reindexed. See
+    /// [`VaultIndexBuilder::resolve_links_eagerly`].
+    fn resolve_all_links(&mut self) {
+        let resolved: Vec<Vec<Option<String>>> = self.notes.values().map(|note| self.resolve_links(note)).collect();
+        for (note, resolved_links) in self.notes.values_mut().zip(resolved) {
+            note.resolved_links = resolved_links;
+        }
+        self.resolve_links_eagerly = true;
+    }
+
+    /// Returns `base`, or else `base 1`, `base 2` and so on, whichever no
+    /// note in the vault has yet, ignoring case.
+    pub fn unique_name(&self, base: &str) -> String {
+        let taken: HashSet<String> = self.notes.values().map(|note| note.name.to_lowercase()).collect();
+        if !taken.contains(&base.to_lowercase()) {
+            return base.to_string();
+        }
+        (1..)
+            .map(|i| format!("{} {}", base, i))
+            .find(|name| !taken.contains(&name.to_lowercase()))
+            .expect("Ran out of note names!")
+    }
+
+    /// Returns the wikilink that `from` would use to link to `to`: its name
+    /// if unique, otherwise its path relative to `from`'s folder.
+    pub fn make_link(&self, from: &str, to: &str) -> Option<String> {
+        let source = self.get_note(from)?;
+        let target = self.get_note(to)?;
+        let unique = self.notes.values().filter(|note| note.name == target.name).count() == 1;
+        if unique {
+            return Some(format!("[[{}]]", target.name));
+        }
+        let from_folder: Vec<&OsStr> = source.local_path.parent().map(|p| p.iter().collect()).unwrap_or_default();
+        let to_path: Vec<&OsStr> = target.local_path.iter().collect();
+        let common = from_folder.iter().zip(&to_path).take_while(|(a, b)| a == b).count();
+        let mut parts: Vec<String> = vec![".."; from_folder.len() - common].into_iter().map(String::from).collect();
+        if parts.is_empty() && to_path.len() - common == 1 {
+            parts.push(".".to_string());
+        }
+        parts.extend(to_path[common..].iter().map(|part| part.to_string_lossy().to_string()));
+        Some(format!("[[{}]]", parts.join("/")))
+    }
+
+    /// Returns every external (`http` or `https`) link in the vault, as the
+    /// name of the note it's in and its URL, in index order.
+    pub fn external_links(&self) -> Vec<(String, String)> {
+        self.notes.values()
+            .flat_map(|note| note.external_links.iter().map(|url| (note.name.clone(), url.clone())))
+            .collect()
+    }
+
+    /// Returns every note with an external link to the given domain or
+    /// any of its subdomains, so `"example.com"` also matches links to
+    /// `docs.example.com`. Domains are matched ignoring case.
+    pub fn notes_linking_to_domain(&self, domain: &str) -> Vec<&NoteItem> {
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        self.notes.values()
+            .filter(|note| note.external_links.iter().any(|url| {
+                let host = url_host(url).to_lowercase();
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }))
+            .collect()
+    }
+
+    /// Returns every note that links to or embeds the named note, in
+    /// index order.
+    pub fn backlinks(&self, name: &str) -> Vec<&NoteItem> {
+        let Some(target) = self.get_note(name) else {
+            return vec![];
+        };
+        self.notes.values()
+            .filter(|note| note.links.iter().chain(&note.embeds)
+                .any(|link| self.link_target(link).is_some_and(|n| n.local_path == target.local_path)))
+            .collect()
+    }
+
+    /// Returns every note that neither links to (or embeds) another note
+    /// nor is linked to by one. Links to a note's own self don't count,
+    /// and neither do links to attachments or to notes that don't exist.
+    pub fn orphan_notes(&self) -> Vec<&NoteItem> {
+        let mut linked: HashSet<&Path> = HashSet::new();
+        for note in self.notes.values() {
+            for link in note.links.iter().chain(&note.embeds) {
+                if let Some(target) = self.link_target(link) {
+                    if target.local_path != note.local_path {
+                        linked.insert(&note.local_path);
+                        linked.insert(&target.local_path);
+                    }
+                }
+            }
+        }
+        self.notes.values()
+            .filter(|note| !linked.contains(note.local_path.as_path()))
+            .collect()
+    }
+
+    /// Ranks every note by PageRank over its links and embeds, from the
+    /// highest score down.
+    pub fn rank_notes(&self) -> Vec<(&NoteItem, f64)> {
+        let count = self.notes.len();
+        if count == 0 {
+            return vec![];
+        }
+        let positions: HashMap<&Path, usize> = self.notes.values()
+            .enumerate()
+            .map(|(i, note)| (note.local_path.as_path(), i))
+            .collect();
+        let outlinks: Vec<Vec<usize>> = self.notes.values()
+            .map(|note| {
+                let mut targets: Vec<usize> = note.links.iter().chain(&note.embeds)
+                    .filter_map(|link| self.link_target(link))
+                    .filter(|target| target.local_path != note.local_path)
+                    .map(|target| positions[target.local_path.as_path()])
+                    .collect();
+                targets.sort();
+                targets.dedup();
+                targets
+            })
+            .collect();
+
+        let mut scores: Vec<f64> = vec![1.0 / count as f64; count];
+        for _ in 0..RANK_ITERATIONS {
+            // Notes without links share their score with every note.
+            let dangling: f64 = outlinks.iter().zip(&scores)
+                .filter(|(targets, _)| targets.is_empty())
+                .map(|(_, score)| score)
+                .sum();
+            let base = (1.0 - RANK_DAMPING + RANK_DAMPING * dangling) / count as f64;
+            let mut next: Vec<f64> = vec![base; count];
+            for (targets, score) in outlinks.iter().zip(&scores) {
+                for target in targets {
+                    next[*target] += RANK_DAMPING * score / targets.len() as f64;
+                }
+            }
+            scores = next;
+        }
+
+        let mut ranked: Vec<(&NoteItem, f64)> = self.notes.values().zip(scores).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+
+    /// Counts how many notes each pair of tags appears on together, keyed
+    /// in alphabetical order. A tag isn't paired with its own parents.
+    pub fn tag_cooccurrence(&self) -> HashMap<(String, String), usize> {
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for note in self.notes.values() {
+            for (i, a) in note.tags.iter().enumerate() {
+                for b in &note.tags[i + 1..] {
+                    let (first, second) = if a < b { (a, b) } else { (b, a) };
+                    if second.starts_with(&format!("{}/", first)) {
+                        continue;
+                    }
+                    *counts.entry((first.clone(), second.clone())).or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Adds up the sizes of every note and file in the vault, in bytes.
+    /// See [`NoteItem::size_bytes`].
+    pub fn total_size(&self) -> Result<u64, io::Error> {
+        let mut total: u64 = 0;
+        for note in self.notes.values() {
+            total += note.size_bytes()?;
+        }
+        for file in self.files.values() {
+            total += file.size_bytes()?;
+        }
+        Ok(total)
+    }
+
+    /// Counts the notes, files, folders and tags (including parent tags)
+    /// in the vault, from the index alone.
+    pub fn stats(&self) -> VaultStats {
+        VaultStats {
+            notes: self.notes.len(),
+            files: self.files.len(),
+            folders: self.folders.len(),
+            tags: self.tags.len(),
+            total_words: None,
+        }
+    }
+
+    /// Same as [`VaultIndex::stats`], but also counts the words in every
+    /// note. See [`NoteItem::word_count`].
+    pub fn stats_with_words(&self) -> Result<VaultStats, io::Error> {
+        let mut total_words: usize = 0;
+        for note in self.notes.values() {
+            total_words += note.word_count()?;
+        }
+        Ok(VaultStats {
+            total_words: Some(total_words),
+            ..self.stats()
+        })
+    }
+
+    /// Returns the text of the block with the given id (without the `^`)
+    /// in a note, as a block reference like `[[Note#^blockid]]` would show
+    /// it. Returns `None` if the note or block can't be found.
+    pub fn resolve_block(&self, note: &str, block_id: &str) -> Option<String> {
+        let note = self.get_note(note)?;
+        let line = *note.blocks.get(block_id.trim_start_matches('^'))?;
+        markdown::block_text(&self.contents_of(note).ok()?, line)
+    }
+
+    /// Returns the local path of the deepest folder containing all of the
+    /// given notes, or `None` if any of them can't be found.
+    pub fn common_ancestor(&self, notes: &[&str]) -> Option<PathBuf> {
+        let mut folders = notes.iter().map(|name| {
+            let note = self.get_note(name)?;
+            Some(note.local_path.parent().map(Path::to_path_buf).unwrap_or_default())
+        });
+        let mut ancestor: PathBuf = folders.next()??;
+        for folder in folders {
+            let folder = folder?;
+            ancestor = ancestor.components()
+                .zip(folder.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect();
+        }
+        Some(ancestor)
+    }
+
+    /// Returns every note whose property `key` is exactly `value`, in
+    /// index order. See [`Property`] for what counts as equal.
+    pub fn notes_where_property_eq(&self, key: &str, value: &Property) -> Vec<&NoteItem> {
+        self.notes.values()
+            .filter(|note| note.properties.get(key) == Some(value))
+            .collect()
+    }
+
+    /// Returns every note with the given file extension, ignoring case
+    /// and any leading `.`.
+    pub fn notes_of_type(&self, ext: &str) -> Vec<&NoteItem> {
+        let ext = ext.strip_prefix('.').unwrap_or(ext);
+        self.notes.values()
+            .filter(|note| note.file_type.eq_ignore_ascii_case(ext))
+            .collect()
+    }
+
+    /// Returns every note with the given CSS class in its `cssclasses`
+    /// front matter property, in index order.
+    pub fn notes_with_cssclass(&self, class: &str) -> Vec<&NoteItem> {
+        self.notes.values()
+            .filter(|note| note.cssclasses.iter().any(|c| c == class))
+            .collect()
+    }
+
+    /// Returns every note tagged with `tag` or any of its sub-tags, such
+    /// as `#project/alpha` for `project`, without duplicates and in index
+    /// order. A leading `#` on `tag` is ignored.
+    pub fn notes_under_tag(&self, tag: &str) -> Vec<&NoteItem> {
+        let tag = tag.strip_prefix('#').unwrap_or(tag);
+        let prefix = format!("{}/", tag);
+        let keys: HashSet<&String> = self.tags.iter()
+            .filter(|(t, _)| t.as_str() == tag || t.starts_with(&prefix))
+            .flat_map(|(_, notes)| notes)
+            .collect();
+        self.notes.iter()
+            .filter(|(key, _)| keys.contains(key))
+            .map(|(_, note)| note)
+            .collect()
+    }
+
+    /// Groups every note by its first tag one level below `prefix`, with
+    /// untagged notes under `uncategorized`, last.
+    pub fn group_by_tag(&self, prefix: &str) -> IndexMap<String, Vec<&NoteItem>> {
+        let prefix = format!("{}/", prefix.strip_prefix('#').unwrap_or(prefix));
+        let mut groups: IndexMap<String, Vec<&NoteItem>> = IndexMap::new();
+        let mut uncategorized: Vec<&NoteItem> = vec![];
+        for note in self.notes.values() {
+            let group = note.raw_tags.iter()
+                .find_map(|tag| tag.strip_prefix(&prefix)
+                    .and_then(|rest| rest.split('/').next())
+                    .filter(|child| !child.is_empty())
+                    .map(str::to_string));
+            match group {
+                Some(group) => groups.entry(group).or_default().push(note),
+                None => uncategorized.push(note),
+            }
+        }
+        if !uncategorized.is_empty() {
+            groups.entry(UNCATEGORIZED_GROUP.to_string()).or_default().extend(uncategorized);
+        }
+        groups
+    }
+
+    /// Returns every alias in the vault along with the note it belongs
+    /// to, in index order. See [`NoteItem::aliases`].
+    pub fn all_aliases(&self) -> Vec<(String, &NoteItem)> {
+        self.notes.values()
+            .flat_map(|note| note.aliases().into_iter().map(move |alias| (alias, note)))
+            .collect()
+    }
+
+    /// Returns every alias claimed by more than one note, along with those
+    /// notes. Aliases are compared ignoring case, as Obsidian does when
+    /// resolving links; each is reported as first written.
+    pub fn alias_conflicts(&self) -> Vec<(String, Vec<&NoteItem>)> {
+        let mut aliases: IndexMap<String, (String, Vec<&NoteItem>)> = IndexMap::new();
+        for (alias, note) in self.all_aliases() {
+            let entry = aliases.entry(alias.to_lowercase()).or_insert_with(|| (alias, vec![]));
+            if !entry.1.iter().any(|n| n.local_path == note.local_path) {
+                entry.1.push(note);
+            }
+        }
+        aliases
+            .into_values()
+            .filter(|(_, notes)| notes.len() > 1)
+            .collect()
+    }
+
+    /// Returns every name shared by more than one note, along with the
+    /// local paths of the notes using it. Links to these names are
+    /// ambiguous in Obsidian.
+    pub fn duplicate_names(&self) -> Vec<(String, Vec<PathBuf>)> {
+        let mut names: IndexMap<&str, Vec<PathBuf>> = IndexMap::new();
+        for note in self.notes.values() {
+            names.entry(note.name.as_str()).or_default().push(note.local_path.clone());
+        }
+        names
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(name, paths)| (name.to_string(), paths))
+            .collect()
+    }
+
+    /// Checks the whole vault for common problems at once: links and
+    /// embeds that don't resolve, orphan notes, note names shared by more
+    /// than one note, and malformed front matter. See [`HealthReport`].
+    pub fn health_check(&self) -> HealthReport {
+        let resolves = |target: &str| self.link_target(target).is_some() || self.get_item(target).is_some();
+        let mut report = HealthReport::default();
+        for note in self.notes.values() {
+            report.broken_links.extend(note.links.iter()
+                .filter(|link| !resolves(link))
+                .map(|link| (note.name.clone(), link.clone())));
+            report.broken_embeds.extend(note.embeds.iter()
+                .filter(|embed| !resolves(embed))
+                .map(|embed| (note.name.clone(), embed.clone())));
+        }
+        report.orphan_notes = self.orphan_notes().into_iter().map(|note| note.name.clone()).collect();
+        report.duplicate_names = self.duplicate_names();
+        // A duplicate key still leaves the note with its properties; the
+        // other kinds of warning mean the front matter couldn't be read.
+        report.frontmatter_warnings = self.warnings.iter()
+            .filter(|warning| warning.kind != WarningKind::DuplicateKey)
+            .cloned()
+            .collect();
+        report
+    }
+
+    /// Returns the folders with no notes or files in them, in index order.
+    /// With `recursive`, their subfolders must be empty too.
+    pub fn empty_folders(&self, recursive: bool) -> Vec<&FolderItem> {
+        let mut empty: HashSet<PathBuf> = HashSet::new();
+        self.find_empty_folders(0, PathBuf::new(), recursive, &mut empty);
+        self.folders.iter().filter(|folder| empty.contains(&folder.local_path)).collect()
+    }
+
+    /// Walks the tree below a node, recording the local paths of empty
+    /// folders. Returns whether there are any notes or files below it.
+    fn find_empty_folders(&self, node_index: usize, path: PathBuf, recursive: bool, empty: &mut HashSet<PathBuf>) -> bool {
+        let node = &self.tree.arena[node_index];
+        let mut has_items = false;
+        let mut has_nested_items = false;
+        for child in &node.children {
+            let child_node = &self.tree.arena[*child];
+            match child_node.item {
+                ItemType::Folder => {
+                    has_nested_items |= self.find_empty_folders(*child, path.join(&child_node.name), recursive, empty);
+                },
+                _ => has_items = true,
+            }
+        }
+        let is_empty = if recursive { !has_items && !has_nested_items } else { !has_items };
+        if node.item == ItemType::Folder && is_empty {
+            empty.insert(path);
+        }
+        has_items || has_nested_items
+    }
+
+    /// Returns the depth of the most deeply nested item in the vault.
+    /// Items at the root of the vault have a depth of 1, and an empty
+    /// vault has a depth of 0.
+    pub fn max_tree_depth(&self) -> u32 {
+        self.tree.arena.iter().map(|node| node.depth).max().unwrap_or(0)
+    }
+
+    /// Returns the note that comes after the named note in tree order
+    /// (folder by folder, with each folder's contents sorted by name), or
+    /// `None` if it is the last note in the vault.
+    pub fn next_note(&self, name: &str) -> Option<&NoteItem> {
+        self.adjacent_note(name, true, false)
+    }
+
+    /// Returns the note that comes before the named note in tree order,
+    /// or `None` if it is the first note in the vault.
+    pub fn prev_note(&self, name: &str) -> Option<&NoteItem> {
+        self.adjacent_note(name, false, false)
+    }
+
+    /// Like [`VaultIndex::next_note`], but wraps around to the first note
+    /// after the last one.
+    pub fn next_note_wrapping(&self, name: &str) -> Option<&NoteItem> {
+        self.adjacent_note(name, true, true)
+    }
+
+    /// Like [`VaultIndex::prev_note`], but wraps around to the last note
+    /// before the first one.
+    pub fn prev_note_wrapping(&self, name: &str) -> Option<&NoteItem> {
+        self.adjacent_note(name, false, true)
+    }
+
+    /// Searches the bodies of every note for a regular expression; see
+    /// [`SearchHit`].
+    pub fn search_regex(&self, pattern: &str) -> Result<Vec<SearchHit>, regex::Error> {
+        let matcher = Regex::new(pattern)?;
+        let mut hits: Vec<SearchHit> = vec![];
+        for (key, note) in &self.notes {
+            let Ok(contents) = self.contents_of(note) else {
+                continue;
+            };
+            let (_, body) = markdown::split_frontmatter(&contents);
+            let body_start = contents.len() - body.len();
+            for caps in matcher.captures_iter(body) {
+                let whole = caps.get(0).expect("REGEX FAILED");
+                let (line, column) = markdown::line_col(&contents, body_start + whole.start());
+                hits.push(SearchHit {
+                    note: key.clone(),
+                    line,
+                    column,
+                    captures: caps.iter().map(|c| c.map(|c| c.as_str().to_string())).collect(),
+                });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Returns every readable note as a [`SearchDoc`], in index order.
+    pub fn export_documents(&self) -> Vec<SearchDoc> {
+        self.notes.values().filter_map(|note| {
+            let contents = self.contents_of(note).ok()?;
+            let (_, body) = markdown::split_frontmatter(&contents);
+            Some(SearchDoc {
+                name: note.name.clone(),
+                path: note.path.clone(),
+                tags: note.tags.clone(),
+                body_plaintext: markdown::to_plaintext(body),
+            })
+        }).collect()
+    }
+
+    /// Compares the properties of two notes; see [`PropertyDiff`]. Values
+    /// are compared as described for [`Property`]. A note that can't be
+    /// found is taken to have no properties.
+    pub fn property_diff(&self, note_a: &str, note_b: &str) -> PropertyDiff {
+        let empty: IndexMap<String, Property> = IndexMap::new();
+        let a = self.get_note(note_a).map_or(&empty, |note| &note.properties);
+        let b = self.get_note(note_b).map_or(&empty, |note| &note.properties);
+        let mut diff = PropertyDiff::default();
+        for (key, value_a) in a {
+            match b.get(key) {
+                None => diff.only_in_a.push(key.clone()),
+                Some(value_b) if value_a != value_b => diff.changed.push((key.clone(), value_a.clone(), value_b.clone())),
+                Some(_) => {},
+            }
+        }
+        diff.only_in_b = b.keys().filter(|key| !a.contains_key(*key)).cloned().collect();
+        diff
+    }
+
+    /// Returns the other notes in the same folder as the named note, not
+    /// counting those in its subfolders, in tree order. Returns nothing
+    /// if the note can't be found.
+    pub fn siblings(&self, name: &str) -> Vec<&NoteItem> {
+        let Some(note) = self.get_note(name) else {
+            return vec![];
+        };
+        let folder = note.local_path.parent();
+        let mut siblings: Vec<&NoteItem> = self.notes.values()
+            .filter(|other| other.local_path != note.local_path && other.local_path.parent() == folder)
+            .collect();
+        siblings.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+        siblings
+    }
+
+    fn adjacent_note(&self, name: &str, forward: bool, wrap: bool) -> Option<&NoteItem> {
+        let key = self.note_key(name)?;
+        let mut note_keys: Vec<&str> = vec![];
+        self.collect_note_keys(0, PathBuf::new(), &mut note_keys);
+        let pos = note_keys.iter().position(|k| *k == key)?;
+        let adjacent = if forward && pos + 1 < note_keys.len() {
+            pos + 1
+        } else if forward && wrap {
+            0
+        } else if !forward && pos > 0 {
+            pos - 1
+        } else if !forward && wrap {
+            note_keys.len() - 1
+        } else {
+            return None;
+        };
+        self.notes.get(note_keys[adjacent])
+    }
+
+    /// Walks the tree below a node, recording the keys (as in `notes`) of
+    /// the notes below it in tree order.
+    fn collect_note_keys<'a>(&'a self, node_index: usize, path: PathBuf, keys: &mut Vec<&'a str>) {
+        for child in &self.tree.arena[node_index].children {
+            let child_node = &self.tree.arena[*child];
+            let child_path = path.join(&child_node.name);
+            match child_node.item {
+                ItemType::Folder => self.collect_note_keys(*child, child_path, keys),
+                ItemType::Note => {
+                    if let Some(key) = child_path.to_str().and_then(|p| self.filepath_ref.get(p)) {
+                        keys.push(key.as_str());
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
     pub fn query(&self, in_query: &str) -> QueryOutput {
         let query_out = dataview::to_view(in_query, &self);
         //println!("{:?}", query_out);
         query_out
     }
 
-    fn recursive_generate_filefolders(dir_path: &PathBuf, vault_path: &PathBuf, include_obsidian_folder: bool, tree: &mut Tree, tree_parent: usize) -> Vec<FileFolder> {
+    /// Runs a query and renders its output in the given format, whether
+    /// the query asks for a list or a table. See [`QueryOutput::render`].
+    pub fn query_as(&self, in_query: &str, format: OutputFormat) -> String {
+        self.query(in_query).render(format)
+    }
+
+    /// Lazy version of [`VaultIndex::query`], yielding matched notes as
+    /// they're found.
+    pub fn query_iter<'a>(&'a self, in_query: &str) -> Result<impl Iterator<Item = ListItem> + 'a, Vec<String>> {
+        dataview::query_iter(in_query, self)
+    }
+
+    fn recursive_generate_filefolders(dir_path: &Path, vault_path: &PathBuf, options: &VaultIndexBuilder, tree: &mut Tree, tree_parent: usize, warnings: &mut Vec<IndexWarning>) -> Result<Vec<FileFolder>, io::Error> {
         let mut out_filefolders: Vec<FileFolder> = vec![];
-        let fpath = fs::read_dir(dir_path);
-        match fpath {
-            Ok(paths) => {
-                for path in paths {
-                    let child_file = path.unwrap();
-                    if child_file.file_type().unwrap().is_dir() {
-                        if !include_obsidian_folder && &child_file.path().file_name().unwrap().to_str().unwrap() == &".obsidian" {
-                            continue;
-                        }
-                        out_filefolders.push(Self::generate_folder_item(&child_file.path(), vault_path).unwrap());
-                        let idx = tree.add_child(tree_parent, child_file.path().file_name().unwrap().to_str().unwrap().to_owned(), ItemType::Folder).expect("Couldn't find parent in tree!");
-                        let mut children_filepaths = Self::recursive_generate_filefolders(&child_file.path(), vault_path, include_obsidian_folder, tree, idx);
-                        out_filefolders.append(&mut children_filepaths);
-                    } else if child_file.path().extension().unwrap() == "md" {
-                        tree.add_child(tree_parent, child_file.path().file_stem().unwrap().to_str().unwrap().to_owned(), ItemType::Note);
-                        out_filefolders.push(Self::generate_note_item(&child_file.path(), vault_path).unwrap());
-                    } else {
-                        tree.add_child(tree_parent, child_file.path().file_name().unwrap().to_str().unwrap().to_owned(), ItemType::File);
-                        out_filefolders.push(Self::generate_file_item(&child_file.path(), vault_path).unwrap());
-                    }
+        if options.max_depth.is_some_and(|max_depth| tree.arena[tree_parent].depth >= max_depth) {
+            return Ok(out_filefolders);
+        }
+        let mut child_files = options.vault_source().read_dir(dir_path)?;
+        child_files.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+        for child_file in child_files {
+            let child_path = &child_file.path;
+            if child_file.is_dir {
+                if !options.include_obsidian_folder && &child_path.file_name().unwrap().to_str().unwrap() == &".obsidian" {
+                    continue;
                 }
-            },
-            Err(e) => {
-                panic!("{:?}", e);
+                out_filefolders.push(Self::generate_folder_item(child_path, vault_path)?);
+                let idx = tree.add_child(tree_parent, child_path.file_name().unwrap().to_str().unwrap().to_owned(), ItemType::Folder).expect("Couldn't find parent in tree!");
+                let mut children_filepaths = Self::recursive_generate_filefolders(child_path, vault_path, options, tree, idx, warnings)?;
+                out_filefolders.append(&mut children_filepaths);
+            } else if options.is_note(child_path) {
+                tree.add_child(tree_parent, child_path.file_stem().unwrap().to_str().unwrap().to_owned(), ItemType::Note);
+                let (note, note_warnings) = Self::generate_note_item(child_path, vault_path, options)?;
+                options.handle_warnings(note_warnings, warnings)?;
+                out_filefolders.push(note);
+                options.report_progress(child_path);
+            } else if !options.notes_only {
+                tree.add_child(tree_parent, child_path.file_name().unwrap().to_str().unwrap().to_owned(), ItemType::File);
+                out_filefolders.push(Self::generate_file_item(child_path, vault_path, options.source.clone())?);
+                options.report_progress(child_path);
             }
         }
 
-        out_filefolders
+        Ok(out_filefolders)
     }
 
     fn generate_folder_item(path: &PathBuf, vault_path: &PathBuf) -> Result<FileFolder, io::Error> {
@@ -474,15 +2571,17 @@ impl VaultIndex {
         Ok(FileFolder::Folder(fi))
     }
 
-    fn generate_file_item(path: &PathBuf, vault_path: &PathBuf) -> Result<FileFolder, io::Error> {
+    fn generate_file_item(path: &PathBuf, vault_path: &PathBuf, source: Option<SharedSource>) -> Result<FileFolder, io::Error> {
         let name = path.file_name().unwrap().to_str().unwrap().to_owned();
-        let file_type = path.extension().unwrap().to_str().unwrap().to_owned();
+        let file_type = path.extension().unwrap_or_default().to_str().unwrap().to_owned();
         let local_path = path.strip_prefix(vault_path).unwrap().to_path_buf();
         let fi = FileItem {
             name,
             file_type,
             path: path.to_path_buf(),
             local_path,
+            archive: None,
+            source,
         };
         Ok(FileFolder::File(fi))
     }
@@ -505,69 +2604,228 @@ impl VaultIndex {
         out_tags
     }
 
-    fn generate_note_item(path: &PathBuf, vault_path: &PathBuf) -> Result<FileFolder, io::Error> {
-        let name = path.file_stem().unwrap().to_str().unwrap().to_owned();
-        let file_type = path.extension().unwrap().to_str().unwrap().to_owned();
+    /// Reads the tags given by a note's `tags` (or `tag`) property, which
+    /// may be a list or a string of tags separated by commas or spaces.
+    fn frontmatter_tags(properties: &IndexMap<String, Property>) -> Vec<String> {
+        let mut out_tags: Vec<String> = vec![];
+        for key in ["tags", "tag"] {
+            let values: Vec<&Property> = match properties.get(key) {
+                Some(Property::List(l)) => l.iter().collect(),
+                Some(p) => vec![p],
+                None => vec![],
+            };
+            for value in values {
+                if let Property::Text(t) = value {
+                    for tag in t.split([',', ' ']) {
+                        let tag = tag.trim().trim_start_matches('#');
+                        if !tag.is_empty() {
+                            out_tags.push(tag.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        out_tags
+    }
+
+    /// Reads the `cssclasses` (or older `cssclass`) front matter property,
+    /// which may be a list or a string of space or comma separated classes.
+    fn frontmatter_cssclasses(properties: &IndexMap<String, Property>) -> Vec<String> {
+        let mut out_classes: Vec<String> = vec![];
+        for key in ["cssclasses", "cssclass"] {
+            let values: Vec<&Property> = match properties.get(key) {
+                Some(Property::List(l)) => l.iter().collect(),
+                Some(p) => vec![p],
+                None => vec![],
+            };
+            for value in values {
+                if let Property::Text(t) = value {
+                    for class in t.split([',', ' ']) {
+                        let class = class.trim();
+                        if !class.is_empty() && !out_classes.iter().any(|c| c == class) {
+                            out_classes.push(class.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        out_classes
+    }
+
+    fn generate_note_item(path: &Path, vault_path: &Path, options: &VaultIndexBuilder) -> Result<(FileFolder, Vec<IndexWarning>), io::Error> {
         let mut local_path = path.strip_prefix(vault_path).unwrap().to_path_buf();
         local_path.set_extension("");
+        let source = options.vault_source();
+        let modified: Option<SystemTime> = source.metadata(path)?.modified;
+        let file_contents = source.read_to_string(path)?;
+
+        let (mut note, warnings) = Self::parse_note_item(path, local_path, &file_contents, modified, &options.parse_options);
+        note.source = options.source.clone();
+        Ok((FileFolder::Note(Box::new(note)), warnings))
+    }
+
+    /// Parses a note's contents. Problems that don't stop the note from
+    /// being indexed, like malformed front matter, are returned alongside
+    /// it as warnings.
+    fn parse_note_item(path: &Path, local_path: PathBuf, cont: &str, modified: Option<SystemTime>, parse_options: &ParseOptions) -> (NoteItem, Vec<IndexWarning>) {
+        // Found before line endings are normalized, so that it matches
+        // the file as it is.
+        let frontmatter_span = markdown::frontmatter_span(cont);
+        let cont: &str = &parse_options.normalize(cont);
+        let name = path.file_stem().unwrap().to_str().unwrap().to_owned();
+        let file_type = path.extension().unwrap().to_str().unwrap().to_owned();
 
         // Figure out a way to remove these from the loop
-        let tag_matcher = Regex::new(r"(\B#[^\s#]+)").expect("REGEX FAILED");
-        let properties_matcher = Regex::new(r"(---[\w\W]*?---)").expect("REGEX FAILED");
+        // A `#` straight after a word character, as in `page#section`,
+        // doesn't start a tag, and neither does one in a URL or link target.
+        let tag_matcher = Regex::new(r#"\B(#[^\s#()\[\]{}"',.;:!?<>]+)"#).expect("REGEX FAILED");
+        let link_target_matcher = Regex::new(r"\]\([^)]*\)|\[\[[^\]]*\]\]|https?://[^\s)\]>]+").expect("REGEX FAILED");
         let codeblock_matcher = Regex::new(r"```[\w\W]*```").expect("REGEX FAILED");
         let inline_codeblock_matcher = Regex::new(r"[^\n\r`]+?`").expect("REGEX FAILED");
+        let embed_matcher = Regex::new(r"!\[\[([^\]|#^]+)[^\]]*\]\]").expect("REGEX FAILED");
+        let external_link_matcher = Regex::new(r"\[[^\]]*\]\((https?://[^)\s]+)[^)]*\)").expect("REGEX FAILED");
 
         let mut tags: Vec<String> = vec![];
-        let mut properties: HashMap<String, Property> = Default::default();
-        
-        let file_contents = fs::read_to_string(path);
-
-        match file_contents {
-            Ok(cont) => {
-                let mut adj_cont = codeblock_matcher.replace_all(&cont, "").to_string();
-                adj_cont = inline_codeblock_matcher.replace_all(&adj_cont, "").to_string();
-                //println!("{:?}", &cont);
-                for (_, [tag]) in tag_matcher.captures_iter(&adj_cont).map(|c| c.extract()) {
-                    let mut split_tags = Self::tag_splitter(tag.replace('#', ""));
-                    tags.append(&mut split_tags);
-                }
-                tags.sort();
-                tags.dedup();
+        let mut tag_occurrences: Vec<(String, usize, usize)> = vec![];
+        let mut embeds: Vec<String> = vec![];
+        let mut links: Vec<String> = vec![];
+        let mut external_links: Vec<String> = vec![];
+        let mut properties: IndexMap<String, Property> = Default::default();
+        let mut raw_key: HashMap<String, String> = Default::default();
+        let mut warnings: Vec<IndexWarning> = vec![];
 
-                if let Some(ind) = adj_cont.find("---") {
-                    if ind == 0 {
-                        let properties_match = properties_matcher.captures(&adj_cont).unwrap();
-                        properties = Self::generate_properties(properties_match.get(0).unwrap().as_str().replace("---", "").trim()).unwrap();
+        let mut adj_cont = codeblock_matcher.replace_all(cont, "").to_string();
+        adj_cont = inline_codeblock_matcher.replace_all(&adj_cont, "").to_string();
+        //println!("{:?}", &cont);
+        // Tags in the front matter come from its `tags` property instead.
+        // Link targets are left out too, as they can hold heading links.
+        // Code and link targets are blanked out rather than removed, so
+        // that each tag can be found again at the same offset in `cont`.
+        let mut tag_cont = markdown::mask(cont, &codeblock_matcher);
+        tag_cont = markdown::mask(&tag_cont, &inline_codeblock_matcher);
+        tag_cont = markdown::mask(&tag_cont, &link_target_matcher);
+        let (_, tag_body) = markdown::split_frontmatter(&tag_cont);
+        let body_start = tag_cont.len() - tag_body.len();
+        for caps in tag_matcher.captures_iter(tag_body) {
+            let tag = caps.get(1).expect("REGEX FAILED");
+            let raw_tag = tag.as_str().replace('#', "");
+            let (line, column) = markdown::line_col(cont, body_start + tag.start());
+            tag_occurrences.push((raw_tag, line, column));
+        }
 
-                    }
+        for (_, [embed]) in embed_matcher.captures_iter(&adj_cont).map(|c| c.extract()) {
+            embeds.push(embed.trim().to_string());
+        }
+
+        // This takes in the front matter too, so that links in properties
+        // (see `NoteItem::property_links`) are part of the link graph.
+        links.extend(markdown::wikilink_targets(&adj_cont));
+
+        for (_, [url]) in external_link_matcher.captures_iter(&adj_cont).map(|c| c.extract()) {
+            external_links.push(url.to_string());
+        }
+
+        match markdown::split_frontmatter(cont) {
+            (Some(property_yaml), _) => {
+                match Self::generate_properties(property_yaml.trim(), parse_options) {
+                    Ok((p, k)) => {
+                        properties = p;
+                        raw_key = k;
+                        for key in Self::duplicate_keys(property_yaml.trim()) {
+                            warnings.push(IndexWarning {
+                                kind: WarningKind::DuplicateKey,
+                                local_path: local_path.clone(),
+                                message: format!("The front matter of \"{}\" sets \"{}\" more than once; the last value is used", local_path.display(), key),
+                            });
+                        }
+                    },
+                    Err(e) => warnings.push(IndexWarning {
+                        kind: if Self::tab_indentation_error(property_yaml.trim()).is_some() {
+                            WarningKind::TabIndentedFrontmatter
+                        } else {
+                            WarningKind::MalformedFrontmatter
+                        },
+                        local_path: local_path.clone(),
+                        message: format!("Couldn't read the front matter of \"{}\": {}", local_path.display(), e),
+                    }),
                 }
             },
-            Err(e) => {
-                return Err(e);
-            }
+            (None, _) if markdown::has_unclosed_frontmatter(cont) => {
+                warnings.push(IndexWarning {
+                    kind: WarningKind::UnclosedFrontmatter,
+                    local_path: local_path.clone(),
+                    message: format!("The front matter of \"{}\" is never closed with \"---\"", local_path.display()),
+                });
+            },
+            (None, _) => {},
         }
 
-        let fi = NoteItem {
+        let mut raw_tags: Vec<String> = Self::frontmatter_tags(&properties);
+        raw_tags.extend(tag_occurrences.iter().map(|(tag, _, _)| tag.clone()));
+        for tag in &raw_tags {
+            tags.append(&mut Self::tag_splitter(tag.clone()));
+        }
+        tags.sort();
+        tags.dedup();
+        let cssclasses = Self::frontmatter_cssclasses(&properties);
+        let title = match parse_options.title_source {
+            TitleSource::Filename => None,
+            TitleSource::Frontmatter => match properties.get("title") {
+                Some(Property::Text(t)) if !t.trim().is_empty() => Some(t.trim().to_string()),
+                _ => None,
+            },
+            TitleSource::FirstHeading => markdown::first_heading(markdown::split_frontmatter(cont).1).map(str::to_string),
+        }.unwrap_or_else(|| name.clone());
+
+        let note = NoteItem {
             name,
+            title,
             file_type,
             path: path.to_path_buf(),
             local_path,
             properties,
             tags,
-       };
-       Ok(FileFolder::Note(fi))
+            raw_tags,
+            tag_occurrences,
+            frontmatter_span,
+            modified,
+            embeds,
+            links,
+            resolved_links: vec![],
+            external_links,
+            blocks: markdown::block_ids(cont),
+            cssclasses,
+            raw_key,
+            archive: None,
+            source: None,
+            parse_options: parse_options.clone(),
+        };
+        (note, warnings)
     }
 
-    fn generate_properties(property_yaml: &str) -> Result<HashMap<String, Property>, io::Error> {
-        let mut out_properties: HashMap<String, Property> = Default::default();
+    /// Parses front matter into properties. When keys are normalized (see
+    /// [`VaultIndexBuilder::normalize_property_keys`]), also returns each
+    /// normalized key mapped to the key as written.
+    fn generate_properties(property_yaml: &str, parse_options: &ParseOptions) -> Result<(IndexMap<String, Property>, RawKeys), io::Error> {
+        let mut out_properties: IndexMap<String, Property> = Default::default();
+        let mut raw_key: HashMap<String, String> = Default::default();
+        if let Some(tab_error) = Self::tab_indentation_error(property_yaml) {
+            return Err(tab_error);
+        }
         let yaml = YamlLoader::load_from_str(property_yaml);
         match yaml {
             Ok(y) => {
-                if let Yaml::Hash(h) = &y[0] {
+                if let Some(Yaml::Hash(h)) = y.first() {
                     for (key, value) in h.iter() {
-                        let new_prop: Property = Self::parse_yaml_property(value);
+                        let new_prop: Property = Self::parse_yaml_property(value, parse_options.property_parser.as_ref());
                         if let Yaml::String(k) = key {
-                            out_properties.insert(k.to_string(), new_prop);
+                            if parse_options.normalize_property_keys {
+                                let normalized = k.trim().to_lowercase();
+                                raw_key.insert(normalized.clone(), k.to_string());
+                                out_properties.insert(normalized, new_prop);
+                            } else {
+                                out_properties.insert(k.to_string(), new_prop);
+                            }
                         }  
                     }
                 }
@@ -577,23 +2835,95 @@ impl VaultIndex {
             }
         }
 
-        Ok(out_properties)
+        Ok((out_properties, raw_key))
+    }
+
+    /// Points at the first tab used to indent the front matter, which
+    /// yaml_rust doesn't always reject.
+    fn tab_indentation_error(property_yaml: &str) -> Option<io::Error> {
+        property_yaml.lines().enumerate().find_map(|(i, line)| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let column = indent.find('\t')?;
+            Some(io::Error::new(io::ErrorKind::InvalidData, format!(
+                "Line {} of the front matter is indented with a tab (at column {}); YAML only allows spaces for indentation",
+                i + 1, column + 1,
+            )))
+        })
     }
 
-    fn parse_yaml_property(in_prop: &Yaml) -> Property {
+    /// Finds the top-level keys that appear more than once in a YAML map.
+    /// Only the last value given for such a key is kept when parsing.
+    fn duplicate_keys(property_yaml: &str) -> Vec<String> {
+        let mut finder = DuplicateKeyFinder::default();
+        if YamlParser::new(property_yaml.chars()).load(&mut finder, false).is_err() {
+            return vec![];
+        }
+        finder.duplicates
+    }
+
+    fn parse_yaml_property(in_prop: &Yaml, property_parser: Option<&PropertyParser>) -> Property {
         match in_prop {
             Yaml::Real(p) => return Property::Number(p.parse::<f64>().expect("FAILED TO PARSE FLOAT")),
             Yaml::Integer(p) => return Property::Number((*p) as f64),
-            Yaml::String(p) => return Property::Text(p.clone()),
+            Yaml::String(p) => property_parser
+                .and_then(|parser| (parser.0)(p))
+                .unwrap_or_else(|| Property::Text(p.clone())),
             Yaml::Boolean(p) => return Property::Checkbox(*p),
             Yaml::Array(p) => {
                 let mut out_arr: Vec<Property> = vec![];
                 for i in p {
-                    out_arr.push(Self::parse_yaml_property(i));
+                    out_arr.push(Self::parse_yaml_property(i, property_parser));
                 }
                 return Property::List(out_arr)
             },
+            Yaml::Hash(p) => {
+                let mut out_map: IndexMap<String, Property> = IndexMap::new();
+                for (key, value) in p.iter() {
+                    if let Yaml::String(k) = key {
+                        out_map.insert(k.to_string(), Self::parse_yaml_property(value, property_parser));
+                    }
+                }
+                Property::Map(out_map)
+            },
             _ => return Property::Unknown,
         }
     }
 }
+
+/// Watches YAML parser events for keys repeated in the top-level map.
+#[derive(Default)]
+struct DuplicateKeyFinder {
+    /// For each open collection, whether it is a map and, if so, whether
+    /// its next scalar is a key.
+    stack: Vec<(bool, bool)>,
+    keys: HashSet<String>,
+    duplicates: Vec<String>,
+}
+impl DuplicateKeyFinder {
+    fn finish_value(&mut self) {
+        if let Some((true, expecting_key)) = self.stack.last_mut() {
+            *expecting_key = !*expecting_key;
+        }
+    }
+}
+impl EventReceiver for DuplicateKeyFinder {
+    fn on_event(&mut self, ev: Event) {
+        match ev {
+            Event::MappingStart(_) => self.stack.push((true, true)),
+            Event::SequenceStart(_) => self.stack.push((false, false)),
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.finish_value();
+            },
+            Event::Scalar(value, ..) => {
+                if self.stack.len() == 1 && self.stack[0] == (true, true)
+                    && !self.keys.insert(value.clone()) && !self.duplicates.contains(&value) {
+                    self.duplicates.push(value);
+                }
+                self.finish_value();
+            },
+            Event::Alias(_) => self.finish_value(),
+            _ => {},
+        }
+    }
+}
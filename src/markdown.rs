@@ -0,0 +1,333 @@
+use fancy_regex::Regex as FancyRegex;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Splits a note into its front matter (without the `---` delimiters)
+/// and its body. Front matter is only recognised at the start of the
+/// note, though it may follow some whitespace and a single HTML comment,
+/// as some static site generators allow; see [`frontmatter_offset`].
+pub(crate) fn split_frontmatter(cont: &str) -> (Option<&str>, &str) {
+    let Some(rest) = cont[frontmatter_offset(cont)..].strip_prefix("---") else {
+        return (None, cont);
+    };
+    let Some(first_newline) = rest.find('\n') else {
+        return (None, cont);
+    };
+    if !rest[..first_newline].trim().is_empty() {
+        return (None, cont);
+    }
+    let yaml_start = first_newline + 1;
+    let mut line_start = yaml_start;
+    for line in rest[yaml_start..].split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body_start = line_start + line.len();
+            return (Some(&rest[yaml_start..line_start]), &rest[body_start..]);
+        }
+        line_start += line.len();
+    }
+    (None, cont)
+}
+
+/// Finds where a note's front matter would begin: after any leading
+/// whitespace and, if front matter follows it, a single leading
+/// `<!-- -->` comment.
+pub(crate) fn frontmatter_offset(cont: &str) -> usize {
+    let trimmed = cont.trim_start();
+    if let Some(comment) = trimmed.strip_prefix("<!--") {
+        if let Some(end) = comment.find("-->") {
+            let after_comment = comment[end + 3..].trim_start();
+            if after_comment.starts_with("---") {
+                return cont.len() - after_comment.len();
+            }
+        }
+    }
+    cont.len() - trimmed.len()
+}
+
+/// Finds the byte range of a note's front matter, from the start of its
+/// opening `---` to the end of the line holding its closing `---`, line
+/// break included.
+pub(crate) fn frontmatter_span(cont: &str) -> Option<Range<usize>> {
+    let (frontmatter, body) = split_frontmatter(cont);
+    frontmatter?;
+    Some(frontmatter_offset(cont)..cont.len() - body.len())
+}
+
+/// Whether a note opens front matter with `---` but never closes it.
+pub(crate) fn has_unclosed_frontmatter(cont: &str) -> bool {
+    let opened = cont[frontmatter_offset(cont)..].strip_prefix("---")
+        .is_some_and(|rest| rest.lines().next().is_some_and(|l| l.trim().is_empty()));
+    opened && split_frontmatter(cont).0.is_none()
+}
+
+/// Finds each top-level key in front matter (without its delimiters),
+/// with the byte range of the lines holding it and its value. Blank
+/// lines after a value aren't part of it.
+pub(crate) fn frontmatter_keys(yaml: &str) -> Vec<(String, Range<usize>)> {
+    let mut keys: Vec<(String, Range<usize>)> = vec![];
+    let mut line_start = 0;
+    for line in yaml.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let is_continuation = line.starts_with([' ', '\t', '-']) && !line.starts_with("---");
+        if let Some(key) = key_of_line(line) {
+            keys.push((key, line_start..line_end));
+        } else if let Some((_, range)) = keys.last_mut().filter(|_| is_continuation) {
+            range.end = line_end;
+        }
+        line_start = line_end;
+    }
+    keys
+}
+
+/// Reads the key from a line of front matter that starts one, like
+/// `title: Note` or `"my key":`.
+fn key_of_line(line: &str) -> Option<String> {
+    if line.starts_with([' ', '\t', '#', '-']) || line.trim().is_empty() {
+        return None;
+    }
+    let line = line.trim_end();
+    if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = line[1..].find(quote)? + 1;
+        return line[end + 1..].trim_start().starts_with(':').then(|| line[1..end].to_string());
+    }
+    let colon = line.char_indices()
+        .find(|(i, c)| *c == ':' && line[i + 1..].chars().next().is_none_or(char::is_whitespace))?
+        .0;
+    Some(line[..colon].trim_end().to_string())
+}
+
+/// Finds the section of a note body under the given heading, including
+/// the heading line itself. The section runs until the next heading of
+/// the same or a higher level. Headings are matched case-insensitively.
+pub(crate) fn section<'a>(body: &'a str, heading: &str) -> Option<&'a str> {
+    let mut start: Option<(usize, usize)> = None;
+    let mut line_start = 0;
+    for line in body.split_inclusive('\n') {
+        if let Some(level) = heading_level(line) {
+            match start {
+                Some((section_start, section_level)) if level <= section_level => {
+                    return Some(&body[section_start..line_start]);
+                },
+                None if line.trim_start_matches('#').trim().eq_ignore_ascii_case(heading.trim()) => {
+                    start = Some((line_start, level));
+                },
+                _ => {},
+            }
+        }
+        line_start += line.len();
+    }
+    start.map(|(section_start, _)| &body[section_start..])
+}
+
+/// Finds the text of the first level 1 (`# `) heading in a note body,
+/// skipping code blocks.
+pub(crate) fn first_heading(body: &str) -> Option<&str> {
+    let fence_matcher = Regex::new(r"^\s*(```|~~~)").expect("REGEX FAILED");
+    let mut in_code_block = false;
+    for line in body.lines() {
+        if fence_matcher.is_match(line) {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && heading_level(line) == Some(1) {
+            let text = line[1..].trim();
+            if !text.is_empty() {
+                return Some(text);
+            }
+        }
+    }
+    None
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) && line[level..].starts_with([' ', '\t']) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// Finds the block ids (`^blockid` at the end of a line) in a note, with
+/// the index of the line each is on. Ids in the front matter or in code
+/// blocks are skipped.
+pub(crate) fn block_ids(cont: &str) -> HashMap<String, usize> {
+    let fence_matcher = Regex::new(r"^\s*(```|~~~)").expect("REGEX FAILED");
+    let block_id_matcher = Regex::new(r"(?:^|\s)\^([A-Za-z0-9-]+)\s*$").expect("REGEX FAILED");
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut in_code_block = false;
+    let (_, body) = split_frontmatter(cont);
+    let body_start = cont[..cont.len() - body.len()].lines().count();
+    for (i, line) in cont.lines().enumerate().skip(body_start) {
+        if fence_matcher.is_match(line) {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if !in_code_block {
+            if let Some(caps) = block_id_matcher.captures(line) {
+                ids.insert(caps[1].to_string(), i);
+            }
+        }
+    }
+    ids
+}
+
+/// Returns the text of the block whose id is on the given line, without
+/// the id. That's the line itself for a list item, or else the paragraph
+/// the line ends. An id on a line of its own refers to the paragraph
+/// before it.
+pub(crate) fn block_text(cont: &str, line: usize) -> Option<String> {
+    let block_id_matcher = Regex::new(r"(?:^|\s)\^[A-Za-z0-9-]+\s*$").expect("REGEX FAILED");
+    let list_item_matcher = Regex::new(r"^\s*([-*+]|\d+[.)])\s").expect("REGEX FAILED");
+    let lines: Vec<&str> = cont.lines().collect();
+    let (_, body) = split_frontmatter(cont);
+    let body_start = cont[..cont.len() - body.len()].lines().count();
+    if line < body_start {
+        return None;
+    }
+    let marked_line = block_id_matcher.replace(lines.get(line)?, "").to_string();
+    let mut end = line;
+    if marked_line.trim().is_empty() {
+        end = body_start + lines[body_start..line].iter().rposition(|l| !l.trim().is_empty())?;
+    } else if list_item_matcher.is_match(&marked_line) {
+        return Some(marked_line);
+    }
+    let start = lines[body_start..end].iter().rposition(|l| l.trim().is_empty()).map_or(body_start, |i| body_start + i + 1);
+    let mut block: Vec<String> = lines[start..end].iter().map(|l| l.to_string()).collect();
+    block.push(if end == line { marked_line } else { lines[end].to_string() });
+    Some(block.join("\n"))
+}
+
+/// An Obsidian callout, a block quote opened with `> [!kind] Title`.
+/// Returned by [`NoteItem::callouts`](crate::NoteItem::callouts). `kind`
+/// is lowercased, and `line` is the line it starts on, from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Callout {
+    pub kind: String,
+    pub title: Option<String>,
+    pub body: String,
+    pub line: usize,
+}
+
+/// Finds the callouts in a note, in the order they appear. Callouts in
+/// the front matter or in code blocks are skipped, and those nested in
+/// another callout are left in its body.
+pub(crate) fn callouts(cont: &str) -> Vec<Callout> {
+    let fence_matcher = Regex::new(r"^\s*(```|~~~)").expect("REGEX FAILED");
+    let callout_matcher = Regex::new(r"^\s*>\s*\[!([^\]]+)\][+-]?(.*)$").expect("REGEX FAILED");
+    let quote_matcher = Regex::new(r"^\s*> ?").expect("REGEX FAILED");
+    let mut out_callouts: Vec<Callout> = vec![];
+    let mut in_code_block = false;
+    let (_, body) = split_frontmatter(cont);
+    let body_start = cont[..cont.len() - body.len()].lines().count();
+    let lines: Vec<&str> = cont.lines().collect();
+    let mut i = body_start;
+    while i < lines.len() {
+        let line = lines[i];
+        if fence_matcher.is_match(line) {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            if let Some(caps) = callout_matcher.captures(line) {
+                let title = caps[2].trim();
+                let body_lines: Vec<Cow<str>> = lines[i + 1..].iter()
+                    .take_while(|l| quote_matcher.is_match(l))
+                    .map(|l| quote_matcher.replace(l, ""))
+                    .collect();
+                out_callouts.push(Callout {
+                    kind: caps[1].trim().to_lowercase(),
+                    title: (!title.is_empty()).then(|| title.to_string()),
+                    body: body_lines.join("\n"),
+                    line: i,
+                });
+                i += body_lines.len();
+            }
+        }
+        i += 1;
+    }
+    out_callouts
+}
+
+/// Blanks out every match of `matcher` in some text with spaces, one
+/// for each byte, keeping line breaks, so that what's left is at the
+/// same byte offsets as in the original.
+pub(crate) fn mask(text: &str, matcher: &Regex) -> String {
+    matcher.replace_all(text, |caps: &regex::Captures| {
+        caps[0].chars()
+            .map(|c| if c == '\n' { "\n".to_string() } else { " ".repeat(c.len_utf8()) })
+            .collect::<String>()
+    }).into_owned()
+}
+
+/// Converts a byte offset in some text to its line and column, both from
+/// 0. The column counts characters, not bytes.
+pub(crate) fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count(), before[line_start..].chars().count())
+}
+
+/// Finds the targets of the `[[...]]` links in some text, without any
+/// heading, block or display text part. Embeds are skipped.
+pub(crate) fn wikilink_targets(text: &str) -> Vec<String> {
+    let link_matcher = Regex::new(r"(!?)\[\[([^\]|#^]*)[^\]]*\]\]").expect("REGEX FAILED");
+    link_matcher.captures_iter(text)
+        .map(|c| c.extract())
+        .filter(|(_, [bang, link])| bang.is_empty() && !link.trim().is_empty())
+        .map(|(_, [_, link])| link.trim().to_string())
+        .collect()
+}
+
+/// Reduces a note body to readable prose by removing markdown syntax.
+pub(crate) fn to_plaintext(body: &str) -> String {
+    let fence_matcher = Regex::new(r"^\s*(```|~~~)").expect("REGEX FAILED");
+    let rule_matcher = Regex::new(r"^\s*([-*_]\s*){3,}$").expect("REGEX FAILED");
+    let heading_matcher = Regex::new(r"^\s{0,3}#{1,6}\s+").expect("REGEX FAILED");
+    let quote_matcher = Regex::new(r"^\s*(>\s?)+").expect("REGEX FAILED");
+    let bullet_matcher = Regex::new(r"^\s*([-*+]|\d+[.)])\s+(\[.\]\s+)?").expect("REGEX FAILED");
+    let callout_matcher = Regex::new(r"^\[![^\]]*\][+-]?\s*").expect("REGEX FAILED");
+    let embed_matcher = Regex::new(r"!\[\[[^\]]*\]\]").expect("REGEX FAILED");
+    let image_matcher = Regex::new(r"!\[([^\]]*)\]\([^)]*\)").expect("REGEX FAILED");
+    let link_matcher = Regex::new(r"\[([^\]]*)\]\([^)]*\)").expect("REGEX FAILED");
+    let aliased_wikilink_matcher = Regex::new(r"\[\[[^\]|]*\|([^\]]*)\]\]").expect("REGEX FAILED");
+    let wikilink_matcher = Regex::new(r"\[\[([^\]#|^]*)[^\]]*\]\]").expect("REGEX FAILED");
+    let inline_code_matcher = Regex::new(r"`([^`]*)`").expect("REGEX FAILED");
+    let emphasis_matcher = FancyRegex::new(r"(\*\*|__|~~|==|\*|(?<!\w)_)(\S(?:.*?\S)?)\1").expect("REGEX FAILED");
+
+    let mut out_lines: Vec<String> = vec![];
+    let mut in_code_block = false;
+    for line in body.lines() {
+        if fence_matcher.is_match(line) {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            out_lines.push(line.to_string());
+            continue;
+        }
+        if rule_matcher.is_match(line) {
+            continue;
+        }
+        let mut out_line = heading_matcher.replace(line, "").to_string();
+        out_line = quote_matcher.replace(&out_line, "").to_string();
+        out_line = bullet_matcher.replace(&out_line, "").to_string();
+        out_line = callout_matcher.replace(&out_line, "").to_string();
+        out_line = embed_matcher.replace_all(&out_line, "").to_string();
+        out_line = image_matcher.replace_all(&out_line, "$1").to_string();
+        out_line = link_matcher.replace_all(&out_line, "$1").to_string();
+        out_line = aliased_wikilink_matcher.replace_all(&out_line, "$1").to_string();
+        out_line = wikilink_matcher.replace_all(&out_line, "$1").to_string();
+        out_line = inline_code_matcher.replace_all(&out_line, "$1").to_string();
+        // Emphasis can be nested (`***bold italic***`), so strip until
+        // nothing changes.
+        loop {
+            let stripped = emphasis_matcher.replace_all(&out_line, "$2").to_string();
+            if stripped == out_line {
+                break;
+            }
+            out_line = stripped;
+        }
+        out_lines.push(out_line.trim_end().to_string());
+    }
+
+    out_lines.join("\n").trim().to_string()
+}
@@ -0,0 +1,118 @@
+use serde::{ Deserialize, Serialize };
+use std::path::Path;
+
+use crate::VaultIndex;
+
+/// Settings for Obsidian's graph view, read from `.obsidian/graph.json`
+/// by [`VaultIndex::graph_settings`]. Settings missing from the file take
+/// their default values.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GraphSettings {
+    pub search: String,
+    pub show_tags: bool,
+    pub show_attachments: bool,
+    pub hide_unresolved: bool,
+    pub show_orphans: bool,
+    pub show_arrow: bool,
+    pub color_groups: Vec<GraphColorGroup>,
+    pub text_fade_multiplier: f64,
+    pub node_size_multiplier: f64,
+    pub line_size_multiplier: f64,
+    pub center_strength: f64,
+    pub repel_strength: f64,
+    pub link_strength: f64,
+    pub link_distance: f64,
+    pub scale: f64,
+}
+
+/// A search query and the color the graph view gives the notes matching
+/// it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphColorGroup {
+    pub query: String,
+    pub color: GraphColor,
+}
+
+/// A color as Obsidian stores it: the alpha, and the red, green and blue
+/// channels packed into one number (`0xRRGGBB`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphColor {
+    pub a: f64,
+    pub rgb: u32,
+}
+
+/// An entry in Obsidian's bookmarks, read from `.obsidian/bookmarks.json`
+/// by [`VaultIndex::bookmarks`]. Paths are relative to the vault root, as
+/// Obsidian writes them. `subpath` is set on a bookmark of a heading
+/// (`#Heading`) or block (`#^blockid`) within a note. Kinds of bookmark
+/// this crate doesn't know are read as `Other`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Bookmark {
+    File {
+        #[serde(default)]
+        path: String,
+        subpath: Option<String>,
+        title: Option<String>,
+    },
+    Folder {
+        #[serde(default)]
+        path: String,
+        title: Option<String>,
+    },
+    Search {
+        #[serde(default)]
+        query: String,
+        title: Option<String>,
+    },
+    Url {
+        #[serde(default)]
+        url: String,
+        title: Option<String>,
+    },
+    Group {
+        title: Option<String>,
+        #[serde(default)]
+        items: Vec<Bookmark>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct BookmarksFile {
+    #[serde(default)]
+    items: Vec<Bookmark>,
+}
+
+impl VaultIndex {
+    /// Reads the vault's graph view settings from `.obsidian/graph.json`.
+    /// Returns `None` if the `.obsidian` folder's files weren't indexed,
+    /// or if the file is missing or malformed.
+    pub fn graph_settings(&self) -> Option<GraphSettings> {
+        let contents = self.read_obsidian_file("graph.json")?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Reads the vault's bookmarks from `.obsidian/bookmarks.json`, in the
+    /// order Obsidian shows them, with groups holding their own. Returns
+    /// an empty list if the `.obsidian` folder's files weren't indexed, or
+    /// if the file is missing or isn't in the format Obsidian uses today.
+    pub fn bookmarks(&self) -> Vec<Bookmark> {
+        self.read_obsidian_file("bookmarks.json")
+            .and_then(|contents| serde_json::from_str::<BookmarksFile>(&contents).ok())
+            .map(|file| file.items)
+            .unwrap_or_default()
+    }
+
+    /// Reads a file from the vault's `.obsidian` folder, if it was
+    /// indexed, wherever the vault is read from.
+    fn read_obsidian_file(&self, name: &str) -> Option<String> {
+        let local_path = Path::new(".obsidian").join(name);
+        let file = self.files.values().find(|file| file.local_path == local_path)?;
+        file.read_to_string().ok()
+    }
+}
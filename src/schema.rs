@@ -0,0 +1,121 @@
+use indexmap::IndexMap;
+
+use crate::{ Property, VaultIndex };
+
+/// The kind of value held by a [`Property`], without the value itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    Text,
+    Number,
+    Checkbox,
+    List,
+    Map,
+    Date,
+    Unknown,
+}
+
+impl Property {
+    /// Returns the [`PropertyKind`] of this property.
+    pub fn kind(&self) -> PropertyKind {
+        match self {
+            Property::Text(_) => PropertyKind::Text,
+            Property::Number(_) => PropertyKind::Number,
+            Property::Checkbox(_) => PropertyKind::Checkbox,
+            Property::List(_) => PropertyKind::List,
+            Property::Map(_) => PropertyKind::Map,
+            Property::Date(_) => PropertyKind::Date,
+            Property::Unknown => PropertyKind::Unknown,
+        }
+    }
+}
+
+/// What a [`Schema`] expects of a single front matter property.
+#[derive(Debug, Clone)]
+pub struct PropertyRule {
+    pub kind: PropertyKind,
+    pub required: bool,
+}
+
+/// A set of expectations for the front matter of every note in a vault,
+/// checked with [`VaultIndex::validate_schema`].
+///
+/// ```rust
+/// use vault_dweller::{ PropertyKind, Schema };
+///
+/// let schema = Schema::new()
+///     .property("status", PropertyKind::Text, true)
+///     .property("priority", PropertyKind::Number, false);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub properties: IndexMap<String, PropertyRule>,
+}
+impl Schema {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a rule for the property `key`. Optional properties are only
+    /// checked for their kind when a note has them.
+    pub fn property(mut self, key: &str, kind: PropertyKind, required: bool) -> Self {
+        self.properties.insert(key.to_string(), PropertyRule { kind, required });
+        self
+    }
+}
+
+/// Why a note failed to match a [`Schema`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationReason {
+    MissingRequired,
+    WrongType {
+        expected: PropertyKind,
+        found: PropertyKind,
+    },
+}
+
+/// A single place where a note didn't match a [`Schema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    pub note_name: String,
+    pub key: String,
+    pub reason: ViolationReason,
+}
+
+impl VaultIndex {
+    /// Checks the front matter of every note in the index against a
+    /// [`Schema`], returning every violation found. An empty `Vec` means
+    /// the whole vault matches.
+    pub fn validate_schema(&self, schema: &Schema) -> Vec<SchemaViolation> {
+        let mut violations: Vec<SchemaViolation> = vec![];
+        for note in self.notes.values() {
+            for (key, rule) in &schema.properties {
+                match note.properties.get(key) {
+                    Some(prop) => {
+                        // Dates in front matter are read as text.
+                        let is_date = rule.kind == PropertyKind::Date && prop.as_date().is_some();
+                        if prop.kind() != rule.kind && !is_date {
+                            violations.push(SchemaViolation {
+                                note_name: note.name.clone(),
+                                key: key.clone(),
+                                reason: ViolationReason::WrongType {
+                                    expected: rule.kind,
+                                    found: prop.kind(),
+                                },
+                            });
+                        }
+                    },
+                    None => {
+                        if rule.required {
+                            violations.push(SchemaViolation {
+                                note_name: note.name.clone(),
+                                key: key.clone(),
+                                reason: ViolationReason::MissingRequired,
+                            });
+                        }
+                    },
+                }
+            }
+        }
+        violations
+    }
+}
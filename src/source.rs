@@ -0,0 +1,75 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Where a vault's files are read from, for indexing a vault from
+/// somewhere other than the disk; see [`VaultIndexBuilder::source`].
+///
+/// [`VaultIndexBuilder::source`]: crate::VaultIndexBuilder::source
+pub trait VaultSource: Send + Sync {
+    /// Lists the entries directly inside a directory, in any order.
+    fn read_dir(&self, path: &Path) -> Result<Vec<VaultEntry>, io::Error>;
+    /// Reads a whole file as a string.
+    fn read_to_string(&self, path: &Path) -> Result<String, io::Error>;
+    /// Gets the metadata of a file or directory.
+    fn metadata(&self, path: &Path) -> Result<VaultMetadata, io::Error>;
+}
+
+/// An entry in a directory, returned by [`VaultSource::read_dir`].
+#[derive(Debug, Clone)]
+pub struct VaultEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+/// The metadata of a file or directory, returned by
+/// [`VaultSource::metadata`].
+#[derive(Debug, Clone)]
+pub struct VaultMetadata {
+    pub is_dir: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// The default [`VaultSource`], which reads from the disk with `std::fs`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFs;
+
+impl VaultSource for StdFs {
+    fn read_dir(&self, path: &Path) -> Result<Vec<VaultEntry>, io::Error> {
+        let mut entries: Vec<VaultEntry> = vec![];
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            entries.push(VaultEntry {
+                path: entry.path(),
+                is_dir: entry.file_type()?.is_dir(),
+            });
+        }
+        Ok(entries)
+    }
+
+    fn read_to_string(&self, path: &Path) -> Result<String, io::Error> {
+        fs::read_to_string(path)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<VaultMetadata, io::Error> {
+        let metadata = fs::metadata(path)?;
+        Ok(VaultMetadata {
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// A [`VaultSource`] shared between a builder and the notes it indexes.
+#[derive(Clone)]
+pub(crate) struct SharedSource(pub(crate) Arc<dyn VaultSource>);
+impl fmt::Debug for SharedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedSource")
+    }
+}
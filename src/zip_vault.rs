@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ self, Read };
+use std::path::{ Path, PathBuf };
+use zip::ZipArchive;
+
+use crate::{ DEFAULT_NOTE_EXTENSION, FileFolder, FileItem, FolderItem, IndexWarning, ItemType, Tree, VaultIndex, has_extension };
+
+impl VaultIndex {
+    /// Creates a new [`VaultIndex`] from a vault stored at the top level of
+    /// a zip archive, reading notes straight from the archive.
+    pub fn from_zip(path: &Path) -> Result<Self, io::Error> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let name = path.file_stem().unwrap().to_str().unwrap().to_owned();
+        let mut tree: Tree = Tree::new();
+        tree.arena[0].name = name.clone();
+        let mut file_collection: Vec<FileFolder> = vec![];
+        let mut folder_nodes: HashMap<PathBuf, usize> = HashMap::new();
+        let mut warnings: Vec<IndexWarning> = vec![];
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let entry_path = match entry.enclosed_name() {
+                Some(p) => p,
+                None => continue,
+            };
+            if entry.is_dir() {
+                add_folder(&entry_path, &mut tree, &mut folder_nodes, &mut file_collection);
+                continue;
+            }
+            let parent = match entry_path.parent() {
+                Some(p) => add_folder(p, &mut tree, &mut folder_nodes, &mut file_collection),
+                None => 0,
+            };
+            let file_type = entry_path.extension().unwrap_or_default().to_str().unwrap().to_owned();
+            if has_extension(&entry_path, &[DEFAULT_NOTE_EXTENSION.to_string()]) {
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents)?;
+                let mut local_path = entry_path.clone();
+                local_path.set_extension("");
+                let (mut note, note_warnings) = VaultIndex::parse_note_item(&entry_path, local_path, &contents, None, &Default::default());
+                warnings.extend(note_warnings);
+                note.archive = Some(path.to_path_buf());
+                tree.add_child(parent, note.name.clone(), ItemType::Note);
+                file_collection.push(FileFolder::Note(Box::new(note)));
+            } else {
+                let file_name = entry_path.file_name().unwrap().to_str().unwrap().to_owned();
+                tree.add_child(parent, file_name.clone(), ItemType::File);
+                file_collection.push(FileFolder::File(FileItem {
+                    name: file_name,
+                    file_type,
+                    path: entry_path.clone(),
+                    local_path: entry_path,
+                    archive: Some(path.to_path_buf()),
+                    source: None,
+                }));
+            }
+        }
+
+        Ok(Self::from_filefolders(name, path.to_path_buf(), file_collection, tree, warnings))
+    }
+}
+
+/// Makes sure a folder (and all of its parents) is in the tree, returning
+/// its index. Zip archives don't always have entries for their folders,
+/// so they're created as they are first seen.
+fn add_folder(folder: &Path, tree: &mut Tree, folder_nodes: &mut HashMap<PathBuf, usize>, file_collection: &mut Vec<FileFolder>) -> usize {
+    if folder.as_os_str().is_empty() {
+        return 0;
+    }
+    if let Some(idx) = folder_nodes.get(folder) {
+        return *idx;
+    }
+    let parent = match folder.parent() {
+        Some(p) => add_folder(p, tree, folder_nodes, file_collection),
+        None => 0,
+    };
+    let name = folder.file_name().unwrap().to_str().unwrap().to_owned();
+    let idx = tree.add_child(parent, name.clone(), ItemType::Folder).expect("Couldn't find parent in tree!");
+    folder_nodes.insert(folder.to_path_buf(), idx);
+    file_collection.push(FileFolder::Folder(FolderItem {
+        name,
+        path: folder.to_path_buf(),
+        local_path: folder.to_path_buf(),
+    }));
+    idx
+}
+
+/// Reads the uncompressed size of an entry in a zip archive.
+pub(crate) fn entry_size(archive: &Path, entry: &Path) -> Result<u64, io::Error> {
+    let mut archive = ZipArchive::new(File::open(archive)?)?;
+    let entry_name: Vec<String> = entry.iter().map(|c| c.to_string_lossy().to_string()).collect();
+    let file = archive.by_name(&entry_name.join("/"))?;
+    Ok(file.size())
+}
+
+/// Reads a single entry from a zip archive as a string.
+pub(crate) fn read_entry(archive: &Path, entry: &Path) -> Result<String, io::Error> {
+    let mut archive = ZipArchive::new(File::open(archive)?)?;
+    let entry_name: Vec<String> = entry.iter().map(|c| c.to_string_lossy().to_string()).collect();
+    let mut file = archive.by_name(&entry_name.join("/"))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
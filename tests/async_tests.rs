@@ -0,0 +1,29 @@
+#![cfg(feature = "tokio")]
+use vault_dweller::VaultIndex;
+use std::env;
+use std::path::{ PathBuf };
+
+fn get_vault_path() -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("TestVault");
+	p
+}
+
+#[tokio::test]
+async fn vault_index_can_be_created_async() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new_async(p.to_str(), true).await.expect("Couldn't make Vault Index!");
+	let on_disk = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert!(vi.notes.keys().eq(on_disk.notes.keys()));
+	assert!(vi.files.keys().eq(on_disk.files.keys()));
+	assert_eq!(vi.folders.len(), on_disk.folders.len());
+	let note = vi.get_note("Lorem Ipsum").expect("Couldn't get note!");
+	assert_eq!(note.tags, on_disk.get_note("Lorem Ipsum").unwrap().tags);
+}
+
+#[tokio::test]
+async fn vault_index_async_invalid_vault_path() {
+	let vi = VaultIndex::new_async(Some("tests/argabarga"), true).await;
+	assert_eq!(vi.is_err(), true);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "bincode")]
+use vault_dweller::{ Property, VaultIndex };
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+fn get_vault_path() -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("TestVault");
+	p
+}
+
+fn cache_path(name: &str) -> PathBuf {
+	let mut p = env::temp_dir();
+	p.push("vault_dweller_cache_tests");
+	fs::create_dir_all(&p).unwrap();
+	p.push(name);
+	p
+}
+
+#[test]
+fn cache_round_trips_the_index() {
+	let vi = VaultIndex::new(get_vault_path().to_str(), true).expect("Couldn't make Vault Index!");
+	let path = cache_path("round_trip.bin");
+	vi.save_cache(&path).expect("Couldn't save cache!");
+	let loaded = VaultIndex::load_cache(&path).expect("Couldn't load cache!");
+
+	assert_eq!(loaded.name, vi.name);
+	assert_eq!(loaded.notes.keys().collect::<Vec<_>>(), vi.notes.keys().collect::<Vec<_>>());
+	assert_eq!(loaded.files.keys().collect::<Vec<_>>(), vi.files.keys().collect::<Vec<_>>());
+	assert_eq!(loaded.folders.len(), vi.folders.len());
+	assert_eq!(loaded.tags, vi.tags);
+	assert_eq!(loaded.properties, vi.properties);
+	assert_eq!(loaded.max_tree_depth(), vi.max_tree_depth());
+	for (key, note) in &vi.notes {
+		let loaded_note = &loaded.notes[key];
+		assert_eq!(loaded_note.as_json(), note.as_json());
+		assert_eq!(loaded_note.get_contents().unwrap(), note.get_contents().unwrap());
+	}
+	let note = loaded.get_note("Vivamus Pharetra").unwrap();
+	assert!(matches!(&note.properties["aliases"], Property::List(l) if l.len() == 2));
+}
+
+#[test]
+fn cache_rejects_other_files() {
+	let path = cache_path("not_a_cache.bin");
+	fs::write(&path, "This isn't a cache.").unwrap();
+	let err = VaultIndex::load_cache(&path).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,271 @@
+use vault_dweller::{ VaultIndex, QueryOutput, OutputFormat };
+use std::env;
+use std::path::{ Path, PathBuf };
+
+fn get_vault_path() -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("TestVault");
+	p
+}
+
+fn fixture_vault(name: &str) -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("fixtures");
+	p.push(name);
+	p
+}
+
+fn list_names(output: QueryOutput) -> Vec<String> {
+	match output {
+		QueryOutput::List(items) => items.into_iter().map(|i| i.note_name.unwrap()).collect(),
+		other => {panic!("Query wasn't a list! {:?}", other);}
+	}
+}
+
+#[test]
+fn dataview_can_filter_on_file_folder_and_sort_by_file_name() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST WHERE file.folder = \"Folder A\" SORT file.name DESC"));
+	assert_eq!(names, vec!["Vivamus Pharetra", "Tag Order", "Lorem Ipsum"]);
+	let names = list_names(vi.query("LIST WHERE file.folder != \"Folder A\" SORT file.name"));
+	assert_eq!(names, vec!["This is the Test Vault"]);
+}
+
+#[test]
+fn dataview_can_filter_on_file_tags() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST FROM #Lorem WHERE contains(file.tags, \"#test\")"));
+	assert_eq!(names, vec!["Lorem Ipsum"]);
+}
+
+#[test]
+fn dataview_can_build_table_of_file_fields() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	match vi.query("TABLE file.folder, file.ext FROM #test SORT file.name") {
+		QueryOutput::Table(table) => {
+			assert_eq!(table.head, vec!["File", "file.folder", "file.ext"]);
+			assert_eq!(table.rows, vec![
+				vec!["Lorem Ipsum".to_string(), "Folder A".to_string(), "md".to_string()],
+				vec!["This is the Test Vault".to_string(), "".to_string(), "md".to_string()],
+			]);
+		},
+		other => {panic!("Query wasn't a table! {:?}", other);}
+	}
+}
+
+#[test]
+fn dataview_table_headers_use_as_aliases() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	match vi.query("TABLE file.folder AS \"Folder\", title FROM #test") {
+		QueryOutput::Table(table) => {
+			assert_eq!(table.head, vec!["File", "Folder", "title"]);
+		},
+		other => {panic!("Query wasn't a table! {:?}", other);}
+	}
+}
+
+fn precedence_vault() -> VaultIndex {
+	let p = fixture_vault("precedence");
+	VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!")
+}
+
+#[test]
+fn dataview_and_binds_tighter_than_or() {
+	let vi = precedence_vault();
+	let names = list_names(vi.query("LIST FROM #a OR #b AND #c SORT file.name"));
+	assert_eq!(names, vec!["A", "BC"]);
+	let names = list_names(vi.query("LIST FROM #b AND #c OR #a SORT file.name"));
+	assert_eq!(names, vec!["A", "BC"]);
+}
+
+#[test]
+fn dataview_parentheses_override_precedence() {
+	let vi = precedence_vault();
+	let names = list_names(vi.query("LIST FROM (#a OR #b) AND #c SORT file.name"));
+	assert_eq!(names, vec!["BC"]);
+}
+
+#[test]
+fn dataview_folder_sources_match_globs() {
+	let p = fixture_vault("glob_sources");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST FROM \"Projects/**/active\" SORT file.name"));
+	assert_eq!(names, vec!["One", "Two"]);
+	let names = list_names(vi.query("LIST FROM \"Projects/Alpha\" SORT file.name"));
+	assert_eq!(names, vec!["One", "Three", "Two"]);
+	let names = list_names(vi.query("LIST FROM \"Projects/**\" SORT file.name"));
+	assert_eq!(names, vec!["One", "Three", "Top", "Two"]);
+}
+
+#[test]
+fn dataview_output_resolves_to_notes() {
+	let vi = precedence_vault();
+	let notes = vi.query("LIST FROM #b SORT file.name").resolve_notes(&vi);
+	let paths: Vec<&Path> = notes.iter().map(|n| n.local_path.as_path()).collect();
+	assert_eq!(paths, vec![Path::new("B"), Path::new("BC")]);
+	let notes = vi.query("TABLE file.name FROM #c SORT file.name DESC").resolve_notes(&vi);
+	assert_eq!(notes.iter().map(|n| n.name.as_str()).collect::<Vec<&str>>(), vec!["C", "BC"]);
+	assert!(vi.query("LIST FROM").resolve_notes(&vi).is_empty());
+}
+
+#[test]
+fn dataview_negates_any_source_expression() {
+	let p = fixture_vault("negation");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST FROM !(#a OR \"Folder A\") SORT file.name"));
+	assert_eq!(names, vec!["Other", "Plain"]);
+	let names = list_names(vi.query("LIST FROM #b AND !\"Folder A\""));
+	assert_eq!(names, vec!["Other"]);
+	let names = list_names(vi.query("LIST FROM !!#a"));
+	assert_eq!(names, vec!["A"]);
+}
+
+#[test]
+fn dataview_output_renders_as_markdown() {
+	let vi = precedence_vault();
+	let output = vi.query("LIST FROM #b SORT file.name");
+	if let QueryOutput::List(items) = &output {
+		assert_eq!(items[0].as_wikilink(), Some("[[B]]".to_string()));
+	}
+	assert_eq!(output.to_markdown(), "- [[B]]\n- [[BC]]\n");
+	let output = vi.query("TABLE file.tags AS \"Tags\" FROM #c SORT file.name");
+	assert_eq!(output.to_markdown(), "| File | Tags |\n| --- | --- |\n| [[BC]] | #b, #c |\n| [[C]] | #c |\n");
+}
+
+#[test]
+fn dataview_output_renders_as_csv() {
+	let p = fixture_vault("csv");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let output = vi.query("TABLE summary FROM \"\" SORT file.name");
+	assert_eq!(output.to_csv(), "File,summary\r\nPlain,simple\r\nQuoted,\"Hello, \"\"world\"\"\"\r\n");
+	let output = vi.query("LIST FROM \"\" SORT file.name");
+	assert_eq!(output.to_csv(), "File\r\nPlain\r\nQuoted\r\n");
+}
+
+#[test]
+fn dataview_tag_wildcards_match_sub_tags() {
+	let p = fixture_vault("tag_wildcards");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let mut names = list_names(vi.query("LIST FROM #project/*"));
+	names.sort();
+	assert_eq!(names, vec!["Child", "Frontmatter", "Grandchild"]);
+	let mut names = list_names(vi.query("LIST FROM #project/**"));
+	names.sort();
+	assert_eq!(names, vec!["Child", "Frontmatter", "Grandchild"]);
+	let names = list_names(vi.query("LIST FROM #project/alpha/*"));
+	assert_eq!(names, vec!["Grandchild"]);
+}
+
+#[test]
+fn dataview_limit_truncates_results() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST FROM \"Folder A\" SORT file.name LIMIT 2"));
+	assert_eq!(names, vec!["Lorem Ipsum", "Tag Order"]);
+	let names: Vec<String> = vi.query_iter("LIST FROM \"Folder A\" SORT file.name DESC LIMIT 2")
+		.expect("Couldn't parse query!")
+		.map(|item| item.note_name.unwrap())
+		.collect();
+	assert_eq!(names, vec!["Vivamus Pharetra", "Tag Order"]);
+	let errors = vi.query_iter("LIST WHERE").err().expect("A bad query should give its errors!");
+	assert!(!errors.is_empty());
+}
+
+#[test]
+fn dataview_query_iter_filters_lazily() {
+	let p = fixture_vault("query_iter_order");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	// Until a `WHERE` has been checked against the notes, there's no
+	// telling how many will match; a list worked out up front would know.
+	let mut matches = vi.query_iter("LIST WHERE file.ext = \"md\"").expect("Couldn't parse query!");
+	assert_eq!(matches.size_hint(), (0, Some(20)));
+	matches.next();
+	assert_eq!(matches.size_hint(), (0, Some(19)));
+	// The same goes for a `FROM`.
+	let mut from = vi.query_iter("LIST FROM \"\"").expect("Couldn't parse query!");
+	assert_eq!(from.size_hint(), (0, Some(20)));
+	from.next();
+	assert_eq!(from.size_hint(), (0, Some(19)));
+	// A `SORT` has to see every match first, so it does know.
+	let mut sorted = vi.query_iter("LIST WHERE file.ext = \"md\" SORT file.name").expect("Couldn't parse query!");
+	assert_eq!(sorted.size_hint(), (20, Some(20)));
+	sorted.next();
+	assert_eq!(sorted.size_hint(), (19, Some(19)));
+	let first: Vec<String> = vi.query_iter("LIST WHERE file.ext = \"md\"")
+		.expect("Couldn't parse query!")
+		.take(3)
+		.map(|item| item.note_name.unwrap())
+		.collect();
+	assert_eq!(first, vec!["Note 00", "Note 01", "Note 02"]);
+	assert_eq!(vi.query_iter("LIST WHERE file.ext = \"md\" LIMIT 5").expect("Couldn't parse query!").count(), 5);
+	assert_eq!(list_names(vi.query("LIST WHERE file.ext = \"md\" LIMIT 5")).len(), 5);
+}
+
+#[test]
+fn dataview_query_iter_agrees_with_query() {
+	let vi = precedence_vault();
+	for query in [
+		"LIST FROM #a OR #b AND #c SORT file.name",
+		"LIST FROM (#a OR #b) AND !#c SORT file.name",
+		"LIST FROM #b, \"A\" WHERE file.name != \"BC\" SORT file.name DESC",
+		"TABLE file.tags FROM #c SORT file.name LIMIT 1",
+	] {
+		let names: Vec<String> = vi.query_iter(query)
+			.expect("Couldn't parse query!")
+			.map(|item| item.note_name.unwrap())
+			.collect();
+		let expected: Vec<String> = vi.query(query).resolve_notes(&vi).iter().map(|note| note.name.clone()).collect();
+		assert_eq!(names, expected, "{}", query);
+	}
+}
+
+#[test]
+fn dataview_combines_folder_and_tag_sources() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = list_names(vi.query("LIST FROM \"Folder A\" AND #test"));
+	assert_eq!(names, vec!["Lorem Ipsum"]);
+	let names = list_names(vi.query("LIST FROM #test AND \"Folder A\""));
+	assert_eq!(names, vec!["Lorem Ipsum"]);
+	let names = list_names(vi.query("LIST FROM \"Folder A\" OR #test SORT file.name"));
+	assert_eq!(names, vec!["Lorem Ipsum", "Tag Order", "This is the Test Vault", "Vivamus Pharetra"]);
+	let names = list_names(vi.query("LIST FROM \"Folder A\" AND !#test SORT file.name"));
+	assert_eq!(names, vec!["Tag Order", "Vivamus Pharetra"]);
+}
+
+#[test]
+fn dataview_query_as_renders_each_format() {
+	let vi = precedence_vault();
+	let query = "TABLE file.tags AS \"Tags\" FROM #c SORT file.name";
+	let json: serde_json::Value = serde_json::from_str(&vi.query_as(query, OutputFormat::Json)).unwrap();
+	assert_eq!(json, serde_json::json!([
+		{"File": "BC", "Tags": "#b, #c"},
+		{"File": "C", "Tags": "#c"},
+	]));
+	assert_eq!(vi.query_as(query, OutputFormat::Markdown), "| File | Tags |\n| --- | --- |\n| [[BC]] | #b, #c |\n| [[C]] | #c |\n");
+	assert_eq!(vi.query_as(query, OutputFormat::Csv), "File,Tags\r\nBC,\"#b, #c\"\r\nC,#c\r\n");
+
+	let query = "LIST FROM #b SORT file.name";
+	let json: serde_json::Value = serde_json::from_str(&vi.query_as(query, OutputFormat::Json)).unwrap();
+	assert_eq!(json, serde_json::json!([{"file": "B"}, {"file": "BC"}]));
+	assert_eq!(vi.query_as(query, OutputFormat::Markdown), "- [[B]]\n- [[BC]]\n");
+	assert_eq!(vi.query_as(query, OutputFormat::Csv), "File\r\nB\r\nBC\r\n");
+}
+
+#[test]
+fn dataview_from_unions_comma_separated_sources() {
+	let vi = precedence_vault();
+	let names = list_names(vi.query("LIST FROM #a, #b SORT file.name"));
+	assert_eq!(names, vec!["A", "B", "BC"]);
+	let names = list_names(vi.query("LIST FROM #a,#c AND #b SORT file.name"));
+	assert_eq!(names, vec!["A", "BC"]);
+	let names = list_names(vi.query("LIST FROM #a , \"\" WHERE file.name != \"B\" SORT file.name"));
+	assert_eq!(names, vec!["A", "BC", "C"]);
+	assert!(matches!(vi.query("LIST FROM #a,"), QueryOutput::Err(_)));
+}
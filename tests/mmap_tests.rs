@@ -0,0 +1,26 @@
+#![cfg(feature = "memmap2")]
+use vault_dweller::VaultIndex;
+use std::env;
+use std::fs;
+
+#[test]
+fn mmap_contents_matches_get_contents() {
+	let mut p = env::temp_dir();
+	p.push("vault_dweller_tests");
+	p.push("mmap_contents");
+	if p.exists() {
+		fs::remove_dir_all(&p).unwrap();
+	}
+	fs::create_dir_all(&p).unwrap();
+	let mut contents = String::from("---\ntags: big\n---\n");
+	for i in 0..50_000 {
+		contents.push_str(&format!("Line {} of a rather large note, with some [[Links]] in it.\n", i));
+	}
+	fs::write(p.join("Big.md"), &contents).unwrap();
+
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Big").unwrap();
+	let map = note.mmap_contents().expect("Couldn't map note!");
+	assert_eq!(map.len(), contents.len());
+	assert_eq!(&map[..], note.get_contents().unwrap().as_bytes());
+}
@@ -1,10 +1,39 @@
-use vault_dweller::{ VaultIndex, VaultItem };
+use vault_dweller::{ Bookmark, CacheCapacity, CacheStats, Callout, GraphSettings, HealthReport, ItemType, Property, PropertyJsonOpts, PropertyKind, QueryOutput, Schema, SchemaViolation, SearchDoc, TagSource, TitleSource, VaultEntry, VaultIndex, VaultIndexBuilder, VaultItem, VaultMetadata, VaultSource, VaultStats, ViolationReason, WarningKind };
+use std::collections::HashMap;
 use std::env;
-use std::path::{ PathBuf };
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicUsize, Ordering };
 
 fn get_vault_path() -> PathBuf {
 	let mut p = env::current_dir().unwrap();
-	p.push("tests\\TestVault");
+	p.push("tests");
+	p.push("TestVault");
+	p
+}
+
+fn fixture_vault(name: &str) -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("fixtures");
+	p.push(name);
+	p
+}
+
+fn make_temp_vault(name: &str, files: &[(&str, &str)]) -> PathBuf {
+	let mut p = env::temp_dir();
+	p.push("vault_dweller_tests");
+	p.push(name);
+	if p.exists() {
+		fs::remove_dir_all(&p).unwrap();
+	}
+	fs::create_dir_all(&p).unwrap();
+	for (local_path, contents) in files {
+		let file_path = p.join(local_path);
+		fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+		fs::write(file_path, contents).unwrap();
+	}
 	p
 }
 
@@ -117,4 +146,1575 @@ fn vault_index_can_dataview() {
 	let p = get_vault_path();
 	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
 	vi.query("LIST FROM #Lorem AND (#Ipsum OR #test)");
-}
\ No newline at end of file
+}
+
+#[test]
+fn vault_index_note_keeps_raw_tags() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Tag Order").expect("Couldn't get note!");
+	assert_eq!(note.raw_tags, vec!["b/a".to_string(), "z".to_string()]);
+	assert_eq!(note.tags, vec!["b".to_string(), "b/a".to_string(), "z".to_string()]);
+}
+
+#[test]
+fn vault_index_can_stream_note_lines() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("This is the Test Vault").expect("Couldn't get note!");
+	let line_count = note.lines().expect("Couldn't open note!").count();
+	let contents = note.get_contents().expect("Couldn't read note!");
+	assert_eq!(line_count, contents.lines().count());
+	assert_eq!(line_count, 13);
+}
+
+#[test]
+fn vault_index_can_list_local_paths() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let paths = vi.local_paths();
+	let nested = Path::new("Folder A").join("Lorem Ipsum");
+	assert!(paths.contains(&nested.to_str().unwrap()));
+	let mut sorted = paths.clone();
+	sorted.sort();
+	assert_eq!(paths, sorted);
+}
+
+#[test]
+fn vault_index_respects_max_depth() {
+	let p = get_vault_path();
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.max_depth(Some(1))
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert!(vi.get_note("This is the Test Vault").is_some());
+	assert!(vi.get_note("Lorem Ipsum").is_none());
+	assert!(vi.folders.iter().any(|f| f.name == "Folder A"));
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.max_depth(Some(0))
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert!(vi.notes.is_empty());
+	assert!(vi.files.is_empty());
+	assert!(vi.folders.is_empty());
+}
+
+#[test]
+fn property_can_format_nested_list_for_display() {
+	let prop = Property::List(vec![
+		Property::Text("draft".to_string()),
+		Property::List(vec![Property::Number(2.0), Property::Checkbox(true)]),
+		Property::Number(1.5),
+	]);
+	assert_eq!(prop.as_display_string(), "draft, 2, true, 1.5");
+}
+
+#[test]
+fn vault_index_can_get_next_and_prev_note() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.next_note("Tag Order").unwrap().name, "Vivamus Pharetra");
+	assert_eq!(vi.prev_note("Tag Order").unwrap().name, "Lorem Ipsum");
+	assert!(vi.next_note("This is the Test Vault").is_none());
+	assert!(vi.prev_note("Lorem Ipsum").is_none());
+	assert_eq!(vi.next_note_wrapping("This is the Test Vault").unwrap().name, "Lorem Ipsum");
+	assert_eq!(vi.prev_note_wrapping("Lorem Ipsum").unwrap().name, "This is the Test Vault");
+}
+
+#[test]
+fn next_and_prev_note_step_onto_notes_sharing_a_name() {
+	let p = fixture_vault("adjacent_duplicates");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let nested = vi.next_note_wrapping("Zeta").unwrap();
+	assert_eq!(nested.get_contents().unwrap(), "Nested");
+	assert_eq!(vi.next_note("Nested/Same").unwrap().get_contents().unwrap(), "Root");
+	assert_eq!(vi.prev_note("Same").unwrap().get_contents().unwrap(), "Nested");
+	assert!(vi.prev_note("Nested/Same").is_none());
+}
+
+#[test]
+fn vault_index_can_validate_schema() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let schema = Schema::new()
+		.property("status", PropertyKind::Text, true)
+		.property("title", PropertyKind::Number, false);
+	let violations = vi.validate_schema(&schema);
+	assert!(violations.contains(&SchemaViolation {
+		note_name: "Vivamus Pharetra".to_string(),
+		key: "status".to_string(),
+		reason: ViolationReason::MissingRequired,
+	}));
+	assert!(violations.contains(&SchemaViolation {
+		note_name: "Lorem Ipsum".to_string(),
+		key: "title".to_string(),
+		reason: ViolationReason::WrongType {
+			expected: PropertyKind::Number,
+			found: PropertyKind::Text,
+		},
+	}));
+	assert_eq!(violations.len(), vi.notes.len() + 1);
+}
+
+#[test]
+fn schema_date_rules_accept_dates_written_as_text() {
+	let p = fixture_vault("schema_dates");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let schema = Schema::new().property("due", PropertyKind::Date, true);
+	assert_eq!(vi.validate_schema(&schema), vec![SchemaViolation {
+		note_name: "Undated".to_string(),
+		key: "due".to_string(),
+		reason: ViolationReason::WrongType {
+			expected: PropertyKind::Date,
+			found: PropertyKind::Text,
+		},
+	}]);
+}
+
+#[test]
+fn note_can_be_read_as_plaintext() {
+	let p = fixture_vault("plaintext");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let plaintext = vi.get_note("Formatted").unwrap().as_plaintext().expect("Couldn't read note!");
+	assert_eq!(plaintext, "A Heading\n\nSome bold and italic text with a link and an alias.\n\nfirst item\na task\nnumbered\n\nquoted\n\nlet x = 1;\n\nsnake_case stays");
+}
+
+#[test]
+fn vault_index_can_get_item_by_abs_path() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_item_by_abs_path(&p.join("Folder A").join("Lorem Ipsum.md")).expect("Couldn't get note!");
+	assert_eq!(note.unwrap_note().name, "Lorem Ipsum");
+	let file = vi.get_item_by_abs_path(&p.join("Pasted image 20241116232620.jpg")).expect("Couldn't get file!");
+	assert_eq!(file.unwrap_file().file_type, "jpg");
+	assert!(vi.get_item_by_abs_path(&env::temp_dir().join("Lorem Ipsum.md")).is_none());
+}
+
+#[test]
+fn vault_index_can_find_metadata_only_notes() {
+	let p = fixture_vault("metadata_only");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Stub").unwrap().is_empty_body().unwrap(), true);
+	assert_eq!(vi.get_note("Full").unwrap().is_empty_body().unwrap(), false);
+	assert_eq!(vi.get_note("Empty").unwrap().is_empty_body().unwrap(), true);
+	assert_eq!(vi.get_note("Windows Stub").unwrap().is_empty_body().unwrap(), true);
+	let stubs: Vec<&str> = vi.metadata_only_notes().unwrap().iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(stubs, vec!["Stub", "Windows Stub"]);
+
+	let mut vi = vi;
+	vi.set_virtual_contents("Draft", "---\nstatus: stub\n---\n".to_string());
+	let stubs: Vec<&str> = vi.metadata_only_notes().unwrap().iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(stubs, vec!["Draft", "Stub", "Windows Stub"]);
+}
+
+#[test]
+fn vault_index_note_index_round_trips() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let idx = vi.note_index("Lorem Ipsum").expect("Couldn't find note!");
+	assert_eq!(vi.note_at(idx).unwrap().name, "Lorem Ipsum");
+	assert!(vi.note_index("Folder Z").is_none());
+	assert!(vi.note_at(vi.notes.len()).is_none());
+}
+
+#[test]
+fn vault_index_can_export_subtree() {
+	let p = fixture_vault("export_subtree");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Plan").unwrap().embeds, vec!["diagram.png".to_string()]);
+	let dest = make_temp_vault("export_subtree_dest", &[]);
+	vi.export_subtree("Project", &dest).expect("Couldn't export subtree!");
+	assert!(dest.join("Plan.md").is_file());
+	assert!(dest.join("Sub").join("Details.md").is_file());
+	assert!(dest.join("Sub").join("local.png").is_file());
+	assert!(dest.join("Attachments").join("diagram.png").is_file());
+	assert!(!dest.join("Other.md").exists());
+	assert!(vi.export_subtree("Folder Z", &dest).is_err());
+}
+
+#[test]
+fn vault_index_can_find_duplicate_names() {
+	let p = fixture_vault("duplicate_names");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.notes.len(), 3);
+	assert_eq!(vi.duplicate_names(), vec![
+		("Same".to_string(), vec![Path::new("A").join("Same"), Path::new("B").join("Same")]),
+	]);
+	assert_eq!(vi.get_note("A/Same").unwrap().tags, vec!["first".to_string()]);
+	assert_eq!(vi.get_note("B/Same").unwrap().tags, vec!["second".to_string()]);
+}
+
+#[test]
+fn vault_index_keys_root_note_by_name_when_duplicated() {
+	// "Nested" sorts before "Same.md", so the nested note is indexed first.
+	let p = fixture_vault("duplicate_root_name");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.notes.len(), 2);
+	assert_eq!(vi.get_note("Same").unwrap().tags, vec!["root".to_string()]);
+	assert_eq!(vi.get_note("Nested/Same").unwrap().tags, vec!["nested".to_string()]);
+}
+
+#[test]
+fn vault_index_reports_progress_per_file() {
+	let p = get_vault_path();
+	let count = Arc::new(AtomicUsize::new(0));
+	let callback_count = count.clone();
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.progress_callback(move |_path| {
+			callback_count.fetch_add(1, Ordering::SeqCst);
+		})
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert_eq!(count.load(Ordering::SeqCst), vi.notes.len() + vi.files.len());
+}
+
+#[test]
+fn note_can_be_read_with_frontmatter() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Lorem Ipsum").expect("Couldn't get note!");
+	let parsed = note.read().expect("Couldn't read note!");
+	assert!(!parsed.body.contains("---"));
+	assert!(!parsed.body.contains("title:"));
+	assert!(parsed.body.trim_start().starts_with("#Lorem/Ipsum #test"));
+	assert_eq!(parsed.frontmatter.len(), note.properties.len());
+	assert_eq!(parsed.frontmatter["title"].as_display_string(), "Lorem Ipsum Dolor");
+	assert_eq!(note.properties["title"].as_display_string(), "Lorem Ipsum Dolor");
+}
+
+#[test]
+fn vault_index_sorts_tags_and_names_with_unicode_collation() {
+	let p = fixture_vault("unicode_sorting");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.all_tags(), vec!["Apple", "eagle", "éclair", "zebra"]);
+	assert_eq!(vi.all_note_names(), vec!["Apple", "eagle", "Éclair", "zebra"]);
+}
+
+#[test]
+fn strict_mode_rejects_broken_frontmatter() {
+	let p = fixture_vault("strict_mode");
+	let err = VaultIndexBuilder::new(p.to_str())
+		.strict(true)
+		.build()
+		.expect_err("Strict mode should reject the broken note!");
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	assert!(err.to_string().contains("Broken"));
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.build()
+		.expect("Lenient mode should index the broken note!");
+	let broken = vi.get_note("Broken").expect("Couldn't get note!");
+	assert!(broken.properties.is_empty());
+	assert!(broken.tags.contains(&"still-tagged".to_string()));
+	assert_eq!(vi.get_note("Good").unwrap().properties["title"].as_display_string(), "Fine");
+	assert_eq!(vi.warnings.len(), 1);
+	assert_eq!(vi.warnings[0].kind, WarningKind::MalformedFrontmatter);
+	assert!(vi.warnings[0].message.contains("Broken"));
+}
+
+#[test]
+fn unreadable_note_fails_the_build() {
+	let p = make_temp_vault("unreadable_note", &[("Good.md", "Body")]);
+	fs::write(p.join("Binary.md"), [0xff, 0xfe, 0x00, 0x80]).unwrap();
+	let err = VaultIndex::new(p.to_str(), true).expect_err("A note that isn't UTF-8 should fail the build!");
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn render_note_inlines_embedded_notes() {
+	let p = fixture_vault("render_note");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let rendered = vi.render_note("Outer").expect("Couldn't render note!");
+	assert_eq!(rendered, "Before\nInner body\n## Part\nkept\n### Detail\nalso kept\nAfter ![[picture.png]]\n");
+
+	let looped = vi.render_note("Loop A").expect("Couldn't render note!");
+	assert_eq!(looped, "A\nB\n![[Loop A|alias]]\n");
+	assert!(vi.render_note("Missing").is_err());
+}
+
+#[test]
+fn item_type_reports_notes_files_and_folders() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.item_type("Folder A/Lorem Ipsum"), Some(ItemType::Note));
+	assert_eq!(vi.item_type("Lorem Ipsum"), Some(ItemType::Note));
+	assert_eq!(vi.item_type("Pasted image 20241116232620.jpg"), Some(ItemType::File));
+	assert_eq!(vi.item_type("Folder A"), Some(ItemType::Folder));
+	assert_eq!(vi.item_type("Folder A/Nothing Here"), None);
+}
+
+#[test]
+fn patch_frontmatter_merges_keys_and_reindexes() {
+	let p = make_temp_vault("patch_frontmatter", &[
+		("Note.md", "---\ntitle: Old\nauthor: Someone\ntags: [kept]\n---\nBody text #inline\n"),
+		("Bare.md", "Just a body\n"),
+	]);
+	let mut vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("title".to_string(), Property::Text("New".to_string()));
+	patch.insert("rating".to_string(), Property::Number(4.0));
+	vi.patch_frontmatter("Note", patch).expect("Couldn't patch note!");
+
+	let note = vi.get_note("Note").unwrap();
+	assert_eq!(note.properties["title"].as_display_string(), "New");
+	assert_eq!(note.properties["rating"].as_display_string(), "4");
+	assert_eq!(note.properties["author"].as_display_string(), "Someone");
+	assert_eq!(note.properties["tags"].as_display_string(), "kept");
+	assert!(vi.properties.contains(&"rating".to_string()));
+	assert!(vi.tags["inline"].contains(&"Note".to_string()));
+	let parsed = note.read().unwrap();
+	assert_eq!(parsed.body, "Body text #inline\n");
+	assert_eq!(parsed.frontmatter["title"].as_display_string(), "New");
+
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("done".to_string(), Property::Checkbox(true));
+	vi.patch_frontmatter("Bare", patch).expect("Couldn't patch note!");
+	let bare = vi.get_note("Bare").unwrap();
+	assert_eq!(bare.properties["done"].as_display_string(), "true");
+	assert_eq!(bare.read().unwrap().body, "Just a body\n");
+}
+
+#[test]
+fn patch_frontmatter_keeps_untouched_lines() {
+	let p = make_temp_vault("patch_frontmatter_lines", &[
+		("Note.md", "---\n# Written by hand\nstatus: draft # for now\naliases: [One, Two]\nsummary: |\n  Two lines\n  of text\nnested:\n  b: 1\n  a: 2\n---\nBody\n"),
+		("Windows.md", "---\r\ntitle: Old\r\nkept: yes\r\n---\r\nBody\r\n"),
+		("Upper.md", "---\nStatus: draft\n---\nBody\n"),
+	]);
+	let mut vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("status".to_string(), Property::Text("done".to_string()));
+	vi.patch_frontmatter("Note", patch).expect("Couldn't patch note!");
+	assert_eq!(
+		fs::read_to_string(p.join("Note.md")).unwrap(),
+		"---\n# Written by hand\nstatus: done\naliases: [One, Two]\nsummary: |\n  Two lines\n  of text\nnested:\n  b: 1\n  a: 2\n---\nBody\n",
+	);
+
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("title".to_string(), Property::Text("New".to_string()));
+	patch.insert("added".to_string(), Property::Number(1.0));
+	vi.patch_frontmatter("Windows", patch).expect("Couldn't patch note!");
+	assert_eq!(
+		fs::read_to_string(p.join("Windows.md")).unwrap(),
+		"---\r\ntitle: New\r\nkept: yes\r\nadded: 1\r\n---\r\nBody\r\n",
+	);
+
+	let mut vi = VaultIndexBuilder::new(p.to_str())
+		.normalize_property_keys(true)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("status".to_string(), Property::Text("done".to_string()));
+	vi.patch_frontmatter("Upper", patch).expect("Couldn't patch note!");
+	assert_eq!(fs::read_to_string(p.join("Upper.md")).unwrap(), "---\nStatus: done\n---\nBody\n");
+	assert_eq!(vi.get_note("Upper").unwrap().properties["status"].as_display_string(), "done");
+}
+
+#[test]
+fn patch_frontmatter_patches_virtual_contents() {
+	let p = make_temp_vault("patch_frontmatter_virtual", &[("Note.md", "On disk\n")]);
+	let mut vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	vi.set_virtual_contents("Note", "---\ntitle: Unsaved\n---\nIn the editor\n".to_string());
+	let mut patch: HashMap<String, Property> = HashMap::new();
+	patch.insert("done".to_string(), Property::Checkbox(true));
+	vi.patch_frontmatter("Note", patch).expect("Couldn't patch note!");
+	assert_eq!(vi.get_note_contents("Note").unwrap(), "---\ntitle: Unsaved\ndone: true\n---\nIn the editor\n");
+	assert_eq!(fs::read_to_string(p.join("Note.md")).unwrap(), "On disk\n");
+	assert_eq!(vi.get_note("Note").unwrap().properties["done"].as_display_string(), "true");
+}
+
+#[test]
+fn reindex_note_replaces_its_warnings() {
+	let p = make_temp_vault("reindex_warnings", &[
+		("Broken.md", "---\ntitle: [unclosed\n---\nBody"),
+		("Other.md", "---\ntitle: [also unclosed\n---\nBody"),
+	]);
+	let mut vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.warnings.len(), 2);
+	vi.reindex_note("Broken").unwrap();
+	vi.reindex_note("Broken").unwrap();
+	assert_eq!(vi.warnings.len(), 2);
+
+	fs::write(p.join("Broken.md"), "---\ntitle: Fixed\n---\nBody").unwrap();
+	vi.reindex_note("Broken").unwrap();
+	assert_eq!(vi.warnings.len(), 1);
+	assert_eq!(vi.warnings[0].local_path, Path::new("Other"));
+}
+
+#[test]
+fn word_count_by_folder_sums_notes_in_each_folder() {
+	let p = fixture_vault("word_count_by_folder");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let counts = vi.word_count_by_folder();
+	let chapters: usize = ["Chapter 1", "Chapter 2"].iter()
+		.map(|n| vi.get_note(n).unwrap().word_count().unwrap())
+		.sum();
+	assert_eq!(chapters, 10);
+	assert_eq!(counts[Path::new("Book")], chapters);
+	assert_eq!(counts[Path::new("")], 2);
+	assert_eq!(counts[&Path::new("Book").join("Notes")], 3);
+}
+
+#[test]
+fn graph_settings_are_read_from_obsidian_folder() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let settings: GraphSettings = vi.graph_settings().expect("Couldn't read graph settings!");
+	assert!(settings.show_orphans);
+	assert!(!settings.show_tags);
+	assert_eq!(settings.repel_strength, 10.0);
+	assert_eq!(settings.link_distance, 250.0);
+	assert!(settings.color_groups.is_empty());
+
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert!(vi.graph_settings().is_none());
+
+	let p = fixture_vault("graph_settings");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert!(vi.graph_settings().is_none());
+}
+
+#[test]
+fn orphan_notes_have_no_links_either_way() {
+	let p = fixture_vault("orphan_notes");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Hub").unwrap().links, vec!["Spoke", "Sub/Deep.md"]);
+	let backlinks: Vec<&str> = vi.backlinks("Spoke").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(backlinks, vec!["Hub"]);
+	let orphans: Vec<&str> = vi.orphan_notes().iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(orphans, vec!["Island"]);
+}
+
+#[test]
+fn virtual_contents_override_the_disk_until_cleared() {
+	let p = fixture_vault("virtual_contents");
+	let mut vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	vi.set_virtual_contents("Draft", "---\nstatus: editing\n---\nUnsaved #unsaved".to_string());
+	assert_eq!(vi.get_note_contents("Draft").unwrap(), "---\nstatus: editing\n---\nUnsaved #unsaved");
+	assert!(!vi.tags.contains_key("unsaved"));
+
+	vi.reindex_note("Draft").expect("Couldn't reindex note!");
+	let note = vi.get_note("Draft").unwrap();
+	assert_eq!(note.tags, vec!["unsaved"]);
+	assert_eq!(note.properties["status"].as_display_string(), "editing");
+	assert_eq!(vi.tags["unsaved"], vec!["Draft"]);
+	assert!(!vi.tags.contains_key("saved"));
+	assert_eq!(fs::read_to_string(p.join("Draft.md")).unwrap(), "Saved #saved");
+
+	vi.clear_virtual_contents("Draft");
+	vi.reindex_note("Draft").expect("Couldn't reindex note!");
+	assert_eq!(vi.get_note("Draft").unwrap().tags, vec!["saved"]);
+	assert_eq!(vi.get_note_contents("Draft").unwrap(), "Saved #saved");
+}
+
+#[test]
+fn frontmatter_lists_of_maps_parse_into_maps() {
+	let p = fixture_vault("list_of_maps");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Sources").unwrap();
+	let Property::List(links) = &note.properties["links"] else {
+		panic!("links wasn't a list! {:?}", note.properties["links"]);
+	};
+	assert_eq!(links.len(), 2);
+	let Property::Map(first) = &links[0] else {
+		panic!("links[0] wasn't a map! {:?}", links[0]);
+	};
+	assert_eq!(first["url"].as_display_string(), "https://example.com");
+	assert_eq!(first["title"].as_display_string(), "Example");
+	assert_eq!(links[1].as_display_string(), "url: https://obsidian.md, title: Obsidian, stars: 5");
+	assert_eq!(note.properties["author"].kind(), PropertyKind::Map);
+}
+
+#[test]
+fn max_tree_depth_is_deepest_item() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.max_tree_depth(), 4);
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.max_tree_depth(), 2);
+	let vi = VaultIndex::new(None, false).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.max_tree_depth(), 0);
+}
+
+#[test]
+fn tag_sources_track_where_tags_were_declared() {
+	let p = fixture_vault("tag_sources");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Tagged").unwrap();
+	assert_eq!(note.tags, vec!["inline", "inline/only", "meta", "meta/only", "shared"]);
+	assert_eq!(note.raw_tags, vec!["shared", "meta/only", "shared", "inline/only"]);
+	assert_eq!(note.inline_tags(), vec!["shared", "inline/only"]);
+	let sources = note.tag_sources();
+	assert_eq!(sources["shared"], TagSource::Both);
+	assert_eq!(sources["meta"], TagSource::Frontmatter);
+	assert_eq!(sources["meta/only"], TagSource::Frontmatter);
+	assert_eq!(sources["inline/only"], TagSource::Inline);
+	assert_eq!(sources.len(), 5);
+	assert_eq!(vi.tags["meta"], vec!["Tagged"]);
+	assert_eq!(vi.get_note("Text Tags").unwrap().tags, vec!["one", "two"]);
+}
+
+/// A read-only in-memory filesystem, holding files by their full path.
+struct MemoryFs {
+	files: HashMap<PathBuf, String>,
+}
+
+impl MemoryFs {
+	fn is_dir(&self, path: &Path) -> bool {
+		self.files.keys().any(|f| f != path && f.starts_with(path))
+	}
+}
+
+impl VaultSource for MemoryFs {
+	fn read_dir(&self, path: &Path) -> std::io::Result<Vec<VaultEntry>> {
+		let mut entries: Vec<VaultEntry> = vec![];
+		for file in self.files.keys() {
+			if let Ok(rest) = file.strip_prefix(path) {
+				let child = path.join(rest.components().next().unwrap());
+				if !entries.iter().any(|e| e.path == child) {
+					entries.push(VaultEntry { is_dir: self.is_dir(&child), path: child });
+				}
+			}
+		}
+		Ok(entries)
+	}
+
+	fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+		self.files.get(path).cloned().ok_or(std::io::ErrorKind::NotFound.into())
+	}
+
+	fn metadata(&self, path: &Path) -> std::io::Result<VaultMetadata> {
+		match self.files.get(path) {
+			Some(contents) => Ok(VaultMetadata { is_dir: false, len: contents.len() as u64, modified: None }),
+			None if self.is_dir(path) => Ok(VaultMetadata { is_dir: true, len: 0, modified: None }),
+			None => Err(std::io::ErrorKind::NotFound.into()),
+		}
+	}
+}
+
+#[test]
+fn vault_index_can_read_from_custom_source() {
+	let root = Path::new("memory").join("Vault");
+	let mut files: HashMap<PathBuf, String> = HashMap::new();
+	files.insert(root.join("Home.md"), "---\ntitle: Home\n---\nSee [[Inner]] #home".to_string());
+	files.insert(root.join("Folder").join("Inner.md"), "Inside #nested/tag".to_string());
+	files.insert(root.join("Folder").join("image.png"), String::new());
+
+	let vi = VaultIndexBuilder::new(root.to_str())
+		.source(MemoryFs { files })
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert_eq!(vi.name, "Vault");
+	assert_eq!(vi.notes.len(), 2);
+	assert_eq!(vi.files.len(), 1);
+	assert_eq!(vi.folders.len(), 1);
+	assert_eq!(vi.get_note("Home").unwrap().properties["title"].as_display_string(), "Home");
+	assert_eq!(vi.tags["nested/tag"], vec!["Inner"]);
+	assert_eq!(vi.get_note("Folder/Inner").unwrap().get_contents().unwrap(), "Inside #nested/tag");
+	assert_eq!(vi.get_note_contents("Home").unwrap(), "---\ntitle: Home\n---\nSee [[Inner]] #home");
+	assert_eq!(vi.backlinks("Inner").len(), 1);
+	let lines: Vec<String> = vi.get_note("Home").unwrap().lines().unwrap().map(|l| l.unwrap()).collect();
+	assert_eq!(lines, vec!["---", "title: Home", "---", "See [[Inner]] #home"]);
+	assert!(!root.exists());
+}
+
+#[test]
+fn obsidian_files_are_read_from_custom_source() {
+	let root = Path::new("memory").join("Configured");
+	let mut files: HashMap<PathBuf, String> = HashMap::new();
+	files.insert(root.join("Home.md"), String::new());
+	files.insert(root.join(".obsidian").join("graph.json"), "{ \"showTags\": true }".to_string());
+	files.insert(root.join(".obsidian").join("bookmarks.json"), "{ \"items\": [{ \"type\": \"file\", \"path\": \"Home.md\" }] }".to_string());
+
+	let vi = VaultIndexBuilder::new(root.to_str())
+		.source(MemoryFs { files })
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert!(vi.graph_settings().expect("Couldn't read graph settings!").show_tags);
+	assert_eq!(vi.bookmarks().len(), 1);
+}
+
+#[test]
+fn timeline_sorts_notes_by_date_property() {
+	let p = fixture_vault("timeline");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let timeline = vi.timeline("created");
+	let names: Vec<&str> = timeline.iter().map(|(n, _)| n.name.as_str()).collect();
+	assert_eq!(names, vec!["Earliest", "Middle", "Later"]);
+	assert_eq!(timeline[0].1.to_rfc3339(), "2023-12-31T00:00:00+00:00");
+	assert_eq!(timeline[2].1.to_rfc3339(), "2024-03-01T09:30:00+00:00");
+}
+
+#[test]
+fn duplicate_frontmatter_keys_are_last_wins_with_warning() {
+	let p = fixture_vault("duplicate_keys");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Dupes").unwrap();
+	assert_eq!(note.properties["status"].as_display_string(), "final");
+	assert_eq!(vi.warnings.len(), 1);
+	assert_eq!(vi.warnings[0].kind, WarningKind::DuplicateKey);
+	assert!(vi.warnings[0].message.contains("\"status\""));
+	assert_eq!(vi.warnings[0].local_path, Path::new("Dupes"));
+
+	let err = VaultIndexBuilder::new(p.to_str()).strict(true).build().expect_err("Strict mode should reject duplicate keys!");
+	assert!(err.to_string().contains("\"status\""));
+}
+
+#[test]
+fn get_items_reports_hits_and_misses() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let items = vi.get_items(&["Lorem Ipsum", "Folder A/Vivamus Pharetra", "Pasted image 20241116232620.jpg", "Nowhere", "Folder A/Nothing"]);
+	assert_eq!(items.len(), 5);
+	assert_eq!(items["Lorem Ipsum"].as_ref().unwrap().unwrap_note().name, "Lorem Ipsum");
+	assert_eq!(items["Folder A/Vivamus Pharetra"].as_ref().unwrap().unwrap_note().name, "Vivamus Pharetra");
+	assert!(matches!(items["Pasted image 20241116232620.jpg"], Some(VaultItem::File(_))));
+	assert!(items["Nowhere"].is_none());
+	assert!(items["Folder A/Nothing"].is_none());
+}
+
+#[test]
+fn notes_modified_between_filters_on_mtime() {
+	use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+	let p = make_temp_vault("modified_between", &[
+		("Old.md", ""),
+		("January.md", ""),
+		("February.md", ""),
+	]);
+	let day = |d: u64| UNIX_EPOCH + Duration::from_secs(d * 86400);
+	// 2024-01-01 is day 19723 since the epoch.
+	for (name, date) in [("Old.md", day(19000)), ("January.md", day(19733)), ("February.md", day(19760))] {
+		fs::File::options().write(true).open(p.join(name)).unwrap().set_modified(date).unwrap();
+	}
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names: Vec<&str> = vi.notes_modified_between(day(19723), day(19754)).iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(names, vec!["January"]);
+	assert_eq!(vi.notes_modified_between(day(19723), SystemTime::now()).len(), 2);
+
+	let names = match vi.query("LIST WHERE file.mtime >= \"2024-01-01\" AND file.mtime <= \"2024-02-01\"") {
+		QueryOutput::List(items) => items.into_iter().map(|i| i.note_name.unwrap()).collect::<Vec<String>>(),
+		other => panic!("Query wasn't a list! {:?}", other),
+	};
+	assert_eq!(names, vec!["January"]);
+}
+
+#[test]
+fn effective_properties_inherit_from_folder_indexes() {
+	let p = fixture_vault("effective_properties");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let chapter = vi.get_note("Chapter").unwrap().effective_properties(&vi);
+	assert_eq!(chapter["status"].as_display_string(), "done");
+	assert_eq!(chapter["genre"].as_display_string(), "fantasy");
+	assert_eq!(chapter["series"].as_display_string(), "First");
+	assert_eq!(chapter["project"].as_display_string(), "Vault");
+	let loose = vi.get_note("Loose").unwrap().effective_properties(&vi);
+	assert_eq!(loose.len(), 2);
+	assert_eq!(loose["status"].as_display_string(), "root");
+}
+
+#[test]
+fn stats_count_vault_contents() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.stats(), VaultStats {
+		notes: 4,
+		files: 1,
+		folders: 1,
+		tags: 7,
+		total_words: None,
+	});
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let stats = vi.stats_with_words().expect("Couldn't count words!");
+	assert_eq!((stats.notes, stats.files, stats.folders), (4, 10, 4));
+	let words: usize = vi.notes.values().map(|n| n.word_count().unwrap()).sum();
+	assert_eq!(stats.total_words, Some(words));
+	assert!(words > 0);
+}
+
+#[test]
+fn url_fragments_and_link_targets_are_not_tags() {
+	let p = fixture_vault("url_fragments");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Links").unwrap();
+	assert_eq!(note.tags, vec!["also-real", "inline", "real"]);
+	assert_eq!(vi.get_note("Punctuated").unwrap().tags, vec!["dashed", "quoted", "todo"]);
+	assert!(!vi.tags.contains_key("section"));
+	assert!(!vi.tags.contains_key("frag."));
+	assert!(!vi.tags.contains_key("anchor"));
+}
+
+#[test]
+fn block_references_resolve_to_block_text() {
+	let p = fixture_vault("block_references");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Source").unwrap();
+	assert_eq!(note.blocks.len(), 3);
+	assert_eq!(note.blocks["para"], 4);
+	assert_eq!(vi.resolve_block("Source", "para"), Some("Intro line\nsecond line of the paragraph".to_string()));
+	assert_eq!(vi.resolve_block("Source", "^item"), Some("- a list item".to_string()));
+	assert_eq!(vi.resolve_block("Source", "quote"), Some("> A quote".to_string()));
+	assert_eq!(vi.resolve_block("Source", "notablock"), None);
+	assert_eq!(vi.render_note("Embedder").unwrap(), "Quoting: - a list item");
+}
+
+#[test]
+fn common_ancestor_is_deepest_shared_folder() {
+	let p = fixture_vault("common_ancestor");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.common_ancestor(&["One", "Two"]), Some(Path::new("Projects").join("Alpha")));
+	assert_eq!(vi.common_ancestor(&["One", "Three"]), Some(PathBuf::from("Projects")));
+	assert_eq!(vi.common_ancestor(&["Two"]), Some(Path::new("Projects").join("Alpha").join("Deep")));
+	assert_eq!(vi.common_ancestor(&["Three", "Four"]), Some(PathBuf::new()));
+	assert_eq!(vi.common_ancestor(&["One", "Root"]), Some(PathBuf::new()));
+	assert_eq!(vi.common_ancestor(&["One", "Missing"]), None);
+	assert_eq!(vi.common_ancestor(&[]), None);
+}
+
+#[test]
+fn files_can_be_found_with_or_without_extension() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	for lookup in ["Pasted image 20241116232620.jpg", "Pasted image 20241116232620", ".obsidian/graph", ".obsidian/graph.json", ".obsidian/plugins/dataview/main"] {
+		match vi.get_item(lookup) {
+			Some(VaultItem::File(f)) => assert!(f.name.starts_with(lookup.rsplit('/').next().unwrap())),
+			other => panic!("Couldn't find file {:?}: {:?}", lookup, other),
+		}
+	}
+	assert_eq!(vi.item_type("graph"), Some(ItemType::File));
+	assert!(vi.get_item(".obsidian/graph.js").is_none());
+	assert!(matches!(vi.get_item("Lorem Ipsum"), Some(VaultItem::Note(_))));
+}
+
+#[test]
+fn alias_conflicts_find_shared_aliases() {
+	let p = fixture_vault("alias_conflicts");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let aliases: Vec<(String, &str)> = vi.all_aliases().into_iter().map(|(a, n)| (a, n.name.as_str())).collect();
+	assert_eq!(aliases, vec![
+		("Shared".to_string(), "First"),
+		("Only First".to_string(), "First"),
+		("shared".to_string(), "Second"),
+		("Third Alias".to_string(), "Third"),
+		("Only First Again".to_string(), "Third"),
+	]);
+	let conflicts = vi.alias_conflicts();
+	assert_eq!(conflicts.len(), 1);
+	assert_eq!(conflicts[0].0, "Shared");
+	assert_eq!(conflicts[0].1.iter().map(|n| n.name.as_str()).collect::<Vec<&str>>(), vec!["First", "Second"]);
+
+	let vi = VaultIndex::new(get_vault_path().to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Vivamus Pharetra").unwrap().aliases(), vec!["Pharetra", "Commodo"]);
+}
+
+#[test]
+fn notes_with_cssclass_filters_by_class() {
+	let p = fixture_vault("notes_with_cssclass");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Wide").unwrap().cssclasses, vec!["wide", "dark"]);
+	assert_eq!(vi.get_note("Legacy").unwrap().cssclasses, vec!["wide", "print"]);
+	assert!(vi.get_note("Plain").unwrap().cssclasses.is_empty());
+	let mut wide: Vec<&str> = vi.notes_with_cssclass("wide").iter().map(|n| n.name.as_str()).collect();
+	wide.sort();
+	assert_eq!(wide, vec!["Legacy", "Wide"]);
+	assert_eq!(vi.notes_with_cssclass("dark").len(), 1);
+	assert!(vi.notes_with_cssclass("missing").is_empty());
+}
+
+#[test]
+fn make_link_uses_shortest_form() {
+	let p = fixture_vault("make_link");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.make_link("From", "Unique"), Some("[[Unique]]".to_string()));
+	assert_eq!(vi.make_link("From", "Notes/Unique"), Some("[[Unique]]".to_string()));
+	assert_eq!(vi.make_link("From", "A/Same"), Some("[[A/Same]]".to_string()));
+	assert_eq!(vi.make_link("Unique", "B/Same"), Some("[[../B/Same]]".to_string()));
+	assert_eq!(vi.make_link("Neighbour", "A/Same"), Some("[[./Same]]".to_string()));
+	assert_eq!(vi.make_link("A/Same", "B/Same"), Some("[[../B/Same]]".to_string()));
+	assert_eq!(vi.make_link("From", "Missing"), None);
+	assert_eq!(vi.make_link("Missing", "Unique"), None);
+}
+
+#[test]
+fn vault_index_builder_uses_custom_property_parser() {
+	let p = fixture_vault("custom_property_parser");
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.property_parser(|value| {
+			let (hours, minutes) = match value.split_once('h') {
+				Some((h, m)) => (h.parse::<f64>().ok()?, m),
+				None => (0.0, value),
+			};
+			let minutes = match minutes.strip_suffix('m') {
+				Some(m) => m.parse::<f64>().ok()?,
+				None if minutes.is_empty() => 0.0,
+				None => return None,
+			};
+			Some(Property::Number(hours * 60.0 + minutes))
+		})
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Task").unwrap();
+	assert!(matches!(note.properties["estimate"], Property::Number(n) if n == 90.0));
+	assert!(matches!(&note.properties["name"], Property::Text(t) if t == "Not a duration"));
+	assert_eq!(note.properties["steps"].as_display_string(), "15, 120");
+	assert!(matches!(note.read().unwrap().frontmatter["estimate"], Property::Number(n) if n == 90.0));
+}
+
+#[test]
+fn vault_index_reports_item_sizes() {
+	let p = fixture_vault("item_sizes");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Note").unwrap().size_bytes().unwrap(), 12);
+	assert_eq!(vi.get_item("data.bin").unwrap().unwrap_file().size_bytes().unwrap(), 10);
+	assert_eq!(vi.get_note("Other").unwrap().size_bytes().unwrap(), 16);
+	assert_eq!(vi.total_size().unwrap(), 12 + 10 + 16);
+}
+
+#[test]
+fn vault_index_builder_can_normalize_property_keys() {
+	let p = fixture_vault("normalize_property_keys");
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.normalize_property_keys(true)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Mixed").unwrap();
+	let mut keys: Vec<&String> = note.properties.keys().collect();
+	keys.sort();
+	assert_eq!(keys, vec!["priority", "status"]);
+	assert_eq!(note.properties["status"].as_display_string(), "done");
+	assert_eq!(note.raw_key["status"], "status");
+	assert_eq!(note.raw_key["priority"], " Priority ");
+	assert!(note.read().unwrap().frontmatter.contains_key("priority"));
+
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Mixed").unwrap();
+	assert!(note.properties.contains_key("Status"));
+	assert!(note.raw_key.is_empty());
+}
+
+#[test]
+fn notes_in_folder_sorted_orders_by_property() {
+	let p = fixture_vault("notes_in_folder_sorted");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = |notes: Vec<&vault_dweller::NoteItem>| notes.iter().map(|n| n.name.clone()).collect::<Vec<String>>();
+	assert_eq!(names(vi.notes_in_folder_sorted("Book", "order", true)), vec![
+		"Chapter One", "Chapter Two", "Chapter Ten", "Appendix", "Notes",
+	]);
+	assert_eq!(names(vi.notes_in_folder_sorted("Book/", "order", false)), vec![
+		"Appendix", "Chapter Ten", "Chapter Two", "Chapter One", "Notes",
+	]);
+	assert_eq!(names(vi.notes_in_folder_sorted("", "order", true)), vec!["Elsewhere"]);
+}
+
+#[test]
+fn vault_index_reads_frontmatter_after_a_leading_comment() {
+	let p = fixture_vault("frontmatter_after_comment");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Commented").unwrap();
+	assert_eq!(note.properties["title"].as_display_string(), "Hello");
+	assert_eq!(note.tags, vec!["inline", "site"]);
+	assert_eq!(note.read().unwrap().body, "# Body #inline\n");
+	assert_eq!(vi.get_note("Indented").unwrap().properties["title"].as_display_string(), "Spaced");
+	assert!(vi.get_note("Two Comments").unwrap().properties.is_empty());
+	assert!(vi.warnings.is_empty());
+}
+
+#[test]
+fn tag_cooccurrence_counts_tag_pairs() {
+	let p = fixture_vault("tag_cooccurrence");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let counts = vi.tag_cooccurrence();
+	let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+	assert_eq!(counts[&pair("cli", "rust")], 3);
+	assert_eq!(counts[&pair("rust", "web")], 1);
+	assert_eq!(counts[&pair("cli", "project/tool")], 1);
+	assert_eq!(counts[&pair("cli", "project")], 1);
+	assert!(!counts.contains_key(&pair("rust", "cli")));
+	assert!(!counts.contains_key(&pair("project", "project/tool")));
+	assert_eq!(counts.len(), 7);
+}
+
+#[test]
+fn vault_index_treats_note_extensions_case_insensitively() {
+	let p = fixture_vault("note_extension_case");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Upper").unwrap();
+	assert_eq!(note.file_type, "MD");
+	assert_eq!(note.properties["title"].as_display_string(), "Shouting");
+	assert_eq!(note.tags, vec!["loud"]);
+	assert!(vi.get_note("Mixed").is_some());
+	assert!(vi.get_note("Other").is_none());
+	assert!(vi.files.contains_key("Other.markdown"));
+	assert!(vi.files.contains_key("README"));
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.note_extensions(&["md", ".markdown"])
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Other").unwrap().tags, vec!["other"]);
+	assert_eq!(vi.notes.len(), 3);
+}
+
+#[test]
+fn tree_to_json_nests_the_vault() {
+	let p = get_vault_path();
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let tree: serde_json::Value = serde_json::from_str(&vi.tree.to_json()).unwrap();
+	assert_eq!(tree, serde_json::json!({
+		"name": "TestVault",
+		"type": "root",
+		"children": [
+			{
+				"name": "Folder A",
+				"type": "folder",
+				"children": [
+					{ "name": "Lorem Ipsum", "type": "note", "children": [] },
+					{ "name": "Tag Order", "type": "note", "children": [] },
+					{ "name": "Vivamus Pharetra", "type": "note", "children": [] },
+				],
+			},
+			{ "name": "Pasted image 20241116232620.jpg", "type": "file", "children": [] },
+			{ "name": "This is the Test Vault", "type": "note", "children": [] },
+		],
+	}));
+}
+
+#[test]
+fn vault_index_finds_external_links() {
+	let p = fixture_vault("external_links");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("Links").unwrap().external_links, vec![
+		"https://docs.example.com/guide?page=2", "http://images.other.org:8080/logo.png",
+	]);
+	assert_eq!(vi.external_links().len(), 4);
+	assert!(vi.external_links().contains(&("Plain".to_string(), "https://EXAMPLE.com".to_string())));
+	let mut names: Vec<&str> = vi.notes_linking_to_domain("example.com").iter().map(|n| n.name.as_str()).collect();
+	names.sort();
+	assert_eq!(names, vec!["Links", "Plain"]);
+	assert_eq!(vi.notes_linking_to_domain("other.org").len(), 1);
+	assert!(vi.notes_linking_to_domain("missing.net").is_empty());
+}
+
+#[test]
+fn unique_name_skips_names_in_use() {
+	let p = fixture_vault("unique_name");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.unique_name("Untitled"), "Untitled 2");
+	assert_eq!(vi.unique_name("Fresh"), "Fresh");
+	assert_eq!(vi.unique_name("UNTITLED 1"), "UNTITLED 1 1");
+}
+
+#[test]
+fn vault_index_normalizes_crlf_line_endings() {
+	let contents = "---\r\nstatus: done\r\n---\r\n# Heading\r\n\r\nFirst paragraph\r\nstill going ^para\r\n\r\n- [ ] a task ^task\r\n![[Other#Part]]\r\n";
+	let p = fixture_vault("crlf_line_endings");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Windows").unwrap();
+	assert_eq!(note.properties["status"].as_display_string(), "done");
+	assert_eq!(note.blocks["para"], 6);
+	assert_eq!(vi.resolve_block("Windows", "para"), Some("First paragraph\nstill going".to_string()));
+	assert_eq!(vi.resolve_block("Windows", "task"), Some("- [ ] a task".to_string()));
+	assert_eq!(note.read().unwrap().body, "# Heading\n\nFirst paragraph\nstill going ^para\n\n- [ ] a task ^task\n![[Other#Part]]\n");
+	assert!(!vi.render_note("Windows").unwrap().contains('\r'));
+	// The note itself is left alone.
+	assert_eq!(note.get_contents().unwrap(), contents);
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.normalize_line_endings(false)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert!(vi.get_note("Windows").unwrap().read().unwrap().body.contains("\r\n"));
+}
+
+#[test]
+fn notes_where_property_eq_matches_exact_values() {
+	let p = fixture_vault("notes_where_property_eq");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let names = |notes: Vec<&vault_dweller::NoteItem>| {
+		let mut names: Vec<String> = notes.iter().map(|n| n.name.clone()).collect();
+		names.sort();
+		names
+	};
+	assert_eq!(names(vi.notes_where_property_eq("status", &Property::Text("done".to_string()))), vec!["Also Done", "Done"]);
+	assert_eq!(names(vi.notes_where_property_eq("points", &Property::Number(3.0))), vec!["Also Done", "Done"]);
+	assert_eq!(names(vi.notes_where_property_eq("status", &Property::List(vec![Property::Text("done".to_string())]))), vec!["Listed"]);
+	assert!(vi.notes_where_property_eq("status", &Property::Text("Done".to_string())).is_empty());
+	assert!(vi.notes_where_property_eq("missing", &Property::Unknown).is_empty());
+}
+
+#[test]
+fn empty_folders_reports_folders_without_items() {
+	let p = make_temp_vault("empty_folders", &[
+		("Full/Note.md", ""),
+		("Nested/Deeper/Note.md", ""),
+	]);
+	fs::create_dir_all(p.join("Hollow").join("Inner").join("Innermost")).unwrap();
+	fs::create_dir_all(p.join("Blank")).unwrap();
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	let paths = |folders: Vec<&vault_dweller::FolderItem>| {
+		let mut paths: Vec<PathBuf> = folders.iter().map(|f| f.local_path.clone()).collect();
+		paths.sort();
+		paths
+	};
+	assert_eq!(paths(vi.empty_folders(true)), vec![
+		PathBuf::from("Blank"),
+		PathBuf::from("Hollow"),
+		Path::new("Hollow").join("Inner"),
+		Path::new("Hollow").join("Inner").join("Innermost"),
+	]);
+	assert_eq!(paths(vi.empty_folders(false)), vec![
+		PathBuf::from("Blank"),
+		PathBuf::from("Hollow"),
+		Path::new("Hollow").join("Inner"),
+		Path::new("Hollow").join("Inner").join("Innermost"),
+		PathBuf::from("Nested"),
+	]);
+}
+
+#[test]
+fn vault_index_can_write_properties_as_json_with_joined_lists() {
+	let p = fixture_vault("properties_json_with");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note = match vi.get_item("Note").expect("Couldn't get note!") {
+		VaultItem::Note(n) => n,
+		_ => panic!("Item wasn't a note!"),
+	};
+	let opts = PropertyJsonOpts {
+		list_separator: Some(", ".to_string()),
+		date_format: Some("%Y-%m-%d".to_string()),
+	};
+	let json: serde_json::Value = serde_json::from_str(&note.properties_as_json_with(&opts)).unwrap();
+	assert_eq!(json["tags"], "alpha, beta");
+	assert_eq!(json["rating"], 4.0);
+	assert_eq!(json["created"], "2024-03-01");
+	assert!(json["nested"].is_array());
+
+	let default_json: serde_json::Value = serde_json::from_str(&note.properties_as_json_with(&PropertyJsonOpts::default())).unwrap();
+	let plain_json: serde_json::Value = serde_json::from_str(&note.properties_as_json()).unwrap();
+	assert_eq!(default_json, plain_json);
+}
+
+#[test]
+fn vault_index_can_get_notes_under_tag() {
+	let p = fixture_vault("notes_under_tag");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let mut names: Vec<&str> = vi.notes_under_tag("project").iter().map(|n| n.name.as_str()).collect();
+	names.sort();
+	assert_eq!(names, vec!["Child", "Grandchild", "Parent"]);
+	let mut names: Vec<&str> = vi.notes_under_tag("#project/alpha").iter().map(|n| n.name.as_str()).collect();
+	names.sort();
+	assert_eq!(names, vec!["Child", "Grandchild"]);
+	assert!(vi.notes_under_tag("missing").is_empty());
+}
+
+#[test]
+fn vault_index_can_read_links_in_properties() {
+	let p = fixture_vault("property_links");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let hub = vi.get_note("Hub").expect("Couldn't get note!");
+	assert_eq!(hub.property_links("related"), vec!["A", "Folder/B"]);
+	assert_eq!(hub.property_links("source"), vec!["A"]);
+	assert!(hub.property_links("count").is_empty());
+	assert!(hub.property_links("missing").is_empty());
+	for target in hub.property_links("related") {
+		assert!(vi.get_note(&target).is_some());
+	}
+	let backlinks: Vec<&str> = vi.backlinks("B").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(backlinks, vec!["Hub"]);
+}
+
+#[test]
+fn vault_index_can_rebuild_note_markdown_with_frontmatter() {
+	let p = make_temp_vault("markdown_with_frontmatter", &[
+		("Original.md", "---\ntitle: Round trip\nrating: 4.5\ncount: 3\ndone: true\ntags:\n  - alpha\n  - beta\nmeta:\n  author: Someone\n---\nThe body."),
+		("Plain.md", "No front matter."),
+	]);
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let original = vi.get_note("Original").expect("Couldn't get note!");
+	let markdown = original.to_markdown_with_frontmatter("A new body.");
+	assert!(markdown.starts_with("---\n"));
+	assert!(markdown.ends_with("\n---\nA new body."));
+	let plain = vi.get_note("Plain").expect("Couldn't get note!");
+	assert_eq!(plain.to_markdown_with_frontmatter("Body"), "Body");
+
+	fs::write(p.join("Copy.md"), &markdown).unwrap();
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let original = vi.get_note("Original").expect("Couldn't get note!");
+	let copy = vi.get_note("Copy").expect("Couldn't get copy!");
+	assert_eq!(copy.properties, original.properties);
+	assert_eq!(copy.tags, original.tags);
+	assert_eq!(copy.read().unwrap().body, "A new body.");
+}
+
+#[test]
+fn vault_index_records_tag_occurrences() {
+	let p = fixture_vault("tag_occurrences");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Note").expect("Couldn't get note!");
+	assert_eq!(note.tag_occurrences(), vec![
+		("alpha".to_string(), 3, 11),
+		("beta/gamma".to_string(), 5, 2),
+		("alpha".to_string(), 5, 19),
+		("delta".to_string(), 9, 4),
+	]);
+}
+
+#[test]
+fn vault_index_can_write_note_json_excluding_properties() {
+	let p = fixture_vault("json_excluding");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Note").expect("Couldn't get note!");
+	let json: serde_json::Value = serde_json::from_str(&note.as_json_excluding(&["api_key", "draft"])).unwrap();
+	let properties = json["properties"].as_object().unwrap();
+	assert!(!properties.contains_key("api_key"));
+	assert!(!properties.contains_key("draft"));
+	assert_eq!(properties["title"], "Public");
+	assert_eq!(json["name"], "Note");
+	assert!(!note.as_json_excluding(&["api_key"]).contains("secret"));
+}
+
+#[test]
+fn vault_index_health_check_reports_each_problem() {
+	let p = fixture_vault("health_check");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let report = vi.health_check();
+	assert_eq!(report.broken_links, vec![("A".to_string(), "Missing".to_string())]);
+	assert_eq!(report.broken_embeds, vec![("A".to_string(), "gone.png".to_string())]);
+	let mut orphans = report.orphan_notes.clone();
+	orphans.sort();
+	assert_eq!(orphans, vec!["Bad", "Lonely"]);
+	assert_eq!(report.duplicate_names.len(), 1);
+	assert_eq!(report.duplicate_names[0].0, "Dup");
+	// Duplicate keys are warned about, but aren't front matter that
+	// couldn't be read at all.
+	assert_eq!(vi.warnings.len(), 4);
+	let kinds: Vec<WarningKind> = report.frontmatter_warnings.iter().map(|w| w.kind).collect();
+	assert_eq!(kinds, vec![WarningKind::UnclosedFrontmatter, WarningKind::MalformedFrontmatter, WarningKind::TabIndentedFrontmatter]);
+	assert!(report.frontmatter_warnings[0].message.contains("Bad"));
+	assert!(report.frontmatter_warnings[1].message.contains("Malformed"));
+	assert!(report.frontmatter_warnings[2].message.contains("Tabbed"));
+	assert_eq!(report.problem_count(), 8);
+	assert!(!report.is_healthy());
+
+	let healthy = fixture_vault("health_check_healthy");
+	let vi = VaultIndex::new(healthy.to_str(), false).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.health_check(), HealthReport::default());
+}
+
+#[test]
+fn vault_index_builder_can_take_titles_from_frontmatter_or_headings() {
+	let p = fixture_vault("title_source");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("2024-03-01 meeting").expect("Couldn't get note!");
+	assert_eq!(note.title, "2024-03-01 meeting");
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.title_source(TitleSource::Frontmatter)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let note = vi.get_note("2024-03-01 meeting").expect("Couldn't get note!");
+	assert_eq!(note.name, "2024-03-01 meeting");
+	assert_eq!(note.title, "Planning Meeting");
+	assert_eq!(vi.get_note("Untitled").unwrap().title, "Untitled");
+
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.title_source(TitleSource::FirstHeading)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert_eq!(vi.get_note("2024-03-01 meeting").unwrap().title, "Agenda");
+	assert_eq!(vi.get_note("Untitled").unwrap().title, "Real Heading");
+}
+
+#[test]
+fn vault_index_ranks_linked_notes_higher() {
+	let p = fixture_vault("rank_notes");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let ranked = vi.rank_notes();
+	assert_eq!(ranked.len(), 6);
+	assert_eq!(ranked[0].0.name, "Hub");
+	assert_eq!(ranked[1].0.name, "A");
+	let score = |name: &str| ranked.iter().find(|(n, _)| n.name == name).unwrap().1;
+	assert!(score("Hub") > score("Leaf"));
+	assert!(score("A") > score("B"));
+	assert!((ranked.iter().map(|(_, s)| s).sum::<f64>() - 1.0).abs() < 1e-9);
+	assert!(VaultIndex::new(None, false).unwrap().rank_notes().is_empty());
+}
+
+#[test]
+fn vault_index_reports_stale_notes() {
+	let p = make_temp_vault("stale_notes", &[
+		("Edited.md", "Before"),
+		("Untouched.md", "Same"),
+		("Deleted.md", "Gone soon"),
+	]);
+	let mut vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert!(vi.stale_notes().unwrap().is_empty());
+
+	let edited = fs::File::options().write(true).open(p.join("Edited.md")).unwrap();
+	edited.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+	fs::remove_file(p.join("Deleted.md")).unwrap();
+	let mut stale = vi.stale_notes().unwrap();
+	stale.sort();
+	assert_eq!(stale, vec!["Deleted", "Edited"]);
+
+	vi.reindex_note("Edited").unwrap();
+	assert_eq!(vi.stale_notes().unwrap(), vec!["Deleted"]);
+}
+
+#[test]
+fn vault_index_keeps_property_order() {
+	let p = make_temp_vault("property_order", &[
+		("Note.md", "---\nzeta: 1\nalpha: two\nmiddle: [a, b]\nbeta: true\nnested:\n  y: 1\n  x: 2\n---\nBody"),
+	]);
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note = vi.get_note("Note").expect("Couldn't get note!");
+	let keys: Vec<&str> = note.properties.keys().map(|k| k.as_str()).collect();
+	assert_eq!(keys, vec!["zeta", "alpha", "middle", "beta", "nested"]);
+	let keys: Vec<String> = note.read().unwrap().frontmatter.keys().cloned().collect();
+	assert_eq!(keys, vec!["zeta", "alpha", "middle", "beta", "nested"]);
+	assert!(note.properties_as_json().starts_with("{\"zeta\":1.0,\"alpha\":\"two\""));
+	assert_eq!(note.properties["nested"].as_display_string(), "y: 1, x: 2");
+
+	let markdown = note.to_markdown_with_frontmatter("Body");
+	fs::write(p.join("Copy.md"), &markdown).unwrap();
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let copy = vi.get_note("Copy").expect("Couldn't get copy!");
+	let keys: Vec<&str> = copy.properties.keys().map(|k| k.as_str()).collect();
+	assert_eq!(keys, vec!["zeta", "alpha", "middle", "beta", "nested"]);
+	assert_eq!(copy.properties["nested"].as_display_string(), "y: 1, x: 2");
+}
+
+#[test]
+fn vault_index_lists_file_extensions() {
+	let p = fixture_vault("file_extensions");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.file_extensions(), vec!["csv", "pdf", "png"]);
+	assert!(VaultIndex::new(None, false).unwrap().file_extensions().is_empty());
+}
+
+#[test]
+fn property_can_humanize_relative_dates() {
+	use chrono::{ Duration, TimeZone, Utc };
+	let now = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+	let relative = |offset: Duration| Property::Date(now + offset).humanize_relative(now).unwrap();
+	assert_eq!(relative(Duration::seconds(-20)), "just now");
+	assert_eq!(relative(Duration::minutes(-5)), "5 minutes ago");
+	assert_eq!(relative(Duration::hours(3)), "in 3 hours");
+	assert_eq!(relative(Duration::days(-1)), "yesterday");
+	assert_eq!(relative(Duration::days(1)), "tomorrow");
+	assert_eq!(relative(Duration::days(2)), "in 2 days");
+	assert_eq!(relative(Duration::days(-3)), "3 days ago");
+	assert_eq!(relative(Duration::days(-15)), "2 weeks ago");
+	assert_eq!(relative(Duration::days(65)), "in 2 months");
+	assert_eq!(relative(Duration::days(-400)), "1 year ago");
+	assert_eq!(Property::Text("2024-03-09".to_string()).humanize_relative(now), Some("yesterday".to_string()));
+	assert_eq!(Property::Text("not a date".to_string()).humanize_relative(now), None);
+	assert_eq!(Property::Number(3.0).humanize_relative(now), None);
+}
+
+#[test]
+fn vault_index_can_get_note_siblings() {
+	let p = fixture_vault("siblings");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let names: Vec<&str> = vi.siblings("Beta").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(names, vec!["Alpha", "Gamma"]);
+	let names: Vec<&str> = vi.siblings("Root").iter().map(|n| n.name.as_str()).collect();
+	assert!(names.is_empty());
+	assert!(vi.siblings("Alone").is_empty());
+	assert!(vi.siblings("Missing").is_empty());
+}
+
+#[test]
+fn vault_index_serializes_paths_with_forward_slashes() {
+	let p = fixture_vault("serialized_paths");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let note: serde_json::Value = serde_json::from_str(&vi.get_note("Note").unwrap().as_json()).unwrap();
+	assert_eq!(note["local_path"], "Folder/Sub/Note");
+	assert!(note["path"].as_str().unwrap().ends_with("Folder/Sub/Note.md"));
+	assert!(!note["path"].as_str().unwrap().contains('\\'));
+	let file = serde_json::to_value(&vi.files["image.png"]).unwrap();
+	assert_eq!(file["local_path"], "Folder/Sub/image.png");
+	let folder = serde_json::to_value(vi.folders.iter().find(|f| f.name == "Sub").unwrap()).unwrap();
+	assert_eq!(folder["local_path"], "Folder/Sub");
+}
+
+#[test]
+fn vault_index_records_frontmatter_span() {
+	let plain = "---\ntitle: Span\n---\nBody";
+	let windows = "---\r\ntitle: Span\r\n---\r\nBody";
+	let commented = "<!-- draft -->\n---\ntitle: Span\n---\n\nBody";
+	let p = fixture_vault("frontmatter_span");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let span = vi.get_note("Plain").unwrap().frontmatter_span().unwrap();
+	assert_eq!(span, 0..20);
+	assert_eq!(&plain[span], "---\ntitle: Span\n---\n");
+	let span = vi.get_note("Windows").unwrap().frontmatter_span().unwrap();
+	assert_eq!(&windows[span.clone()], "---\r\ntitle: Span\r\n---\r\n");
+	assert_eq!(&windows[span.end..], "Body");
+	let span = vi.get_note("Commented").unwrap().frontmatter_span().unwrap();
+	assert_eq!(span, 15..35);
+	assert_eq!(&commented[span], "---\ntitle: Span\n---\n");
+	assert_eq!(vi.get_note("None").unwrap().frontmatter_span(), None);
+}
+
+#[test]
+fn vault_index_builder_can_index_notes_only() {
+	let p = fixture_vault("notes_only");
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.notes_only(true)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	assert!(vi.files.is_empty());
+	assert!(vi.get_note("Nested").is_some());
+	assert_eq!(vi.notes.len(), 2);
+	assert_eq!(vi.folders.len(), 2);
+	assert!(vi.tree.as_flat_vec(0).iter().all(|node| !matches!(node.item, ItemType::File)));
+}
+
+#[test]
+fn vault_index_can_diff_note_properties() {
+	let p = fixture_vault("property_diff");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let diff = vi.property_diff("Draft", "Final");
+	assert_eq!(diff.only_in_a, vec!["reviewer"]);
+	assert_eq!(diff.only_in_b, vec!["published"]);
+	assert_eq!(diff.changed, vec![(
+		"status".to_string(),
+		Property::Text("draft".to_string()),
+		Property::Text("done".to_string()),
+	)]);
+	assert!(!diff.is_empty());
+	assert!(vi.property_diff("Draft", "Draft").is_empty());
+	assert_eq!(vi.property_diff("Draft", "Missing").only_in_a.len(), 4);
+}
+
+#[test]
+fn vault_index_can_search_note_bodies_with_regex() {
+	let p = fixture_vault("search_regex");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let hits = vi.search_regex(r"TODO(?:\((\w+)\))?:").expect("Pattern should compile!");
+	assert_eq!(hits.len(), 2);
+	assert_eq!(hits[0].note, "Tasks");
+	assert_eq!((hits[0].line, hits[0].column), (4, 2));
+	assert_eq!(hits[0].captures, vec![Some("TODO(alice):".to_string()), Some("alice".to_string())]);
+	assert_eq!((hits[1].line, hits[1].column), (5, 2));
+	assert_eq!(hits[1].captures[1], None);
+	assert!(vi.search_regex("nothing matches this").unwrap().is_empty());
+	assert!(vi.search_regex("(unclosed").is_err());
+}
+
+#[test]
+fn vault_index_can_group_notes_by_tag() {
+	let p = fixture_vault("group_by_tag");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let groups = vi.group_by_tag("#status");
+	let groups: Vec<(&str, Vec<&str>)> = groups.iter()
+		.map(|(group, notes)| (group.as_str(), notes.iter().map(|n| n.name.as_str()).collect()))
+		.collect();
+	assert_eq!(groups, vec![
+		("todo", vec!["A", "D"]),
+		("done", vec!["B"]),
+		("doing", vec!["C"]),
+		("uncategorized", vec!["E"]),
+	]);
+}
+
+#[test]
+fn bookmarks_are_read_from_obsidian_folder() {
+	let p = fixture_vault("bookmarks");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.bookmarks(), vec![
+		Bookmark::File { path: "Note.md".to_string(), subpath: None, title: None },
+		Bookmark::File { path: "Note.md".to_string(), subpath: Some("#Heading".to_string()), title: Some("Part".to_string()) },
+		Bookmark::Group { title: Some("Reading".to_string()), items: vec![
+			Bookmark::Folder { path: "Folder".to_string(), title: None },
+			Bookmark::Search { query: "tag:#todo".to_string(), title: None },
+		] },
+		Bookmark::Url { url: "https://example.com".to_string(), title: Some("Example".to_string()) },
+		Bookmark::Other,
+	]);
+
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert!(vi.bookmarks().is_empty());
+
+	let p = fixture_vault("bookmarks_old_format");
+	let vi = VaultIndex::new(p.to_str(), true).expect("Couldn't make Vault Index!");
+	assert!(vi.bookmarks().is_empty());
+}
+
+#[test]
+fn vault_index_content_cache_evicts_least_recently_read() {
+	let p = make_temp_vault("content_cache", &[
+		("A.md", "aaaa"),
+		("B.md", "bbbb"),
+		("C.md", "cccc"),
+	]);
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.content_cache(Some(CacheCapacity::Notes(2)))
+		.build()
+		.expect("Couldn't make Vault Index!");
+	vi.get_note_contents("A").unwrap();
+	vi.get_note_contents("B").unwrap();
+	vi.get_note_contents("A").unwrap();
+	vi.get_note_contents("C").unwrap();
+	assert_eq!(vi.cache_stats(), CacheStats { hits: 1, misses: 3, evictions: 1, notes: 2, bytes: 8 });
+	// B was read least recently, so it went first.
+	fs::write(p.join("A.md"), "changed").unwrap();
+	assert_eq!(vi.get_note_contents("A").unwrap(), "aaaa");
+	vi.get_note_contents("B").unwrap();
+	assert_eq!(vi.cache_stats().misses, 4);
+	assert_eq!(vi.cache_stats().evictions, 2);
+	fs::write(p.join("A.md"), "aaaa").unwrap();
+
+	let mut vi = VaultIndexBuilder::new(p.to_str())
+		.content_cache(Some(CacheCapacity::Bytes(10)))
+		.build()
+		.expect("Couldn't make Vault Index!");
+	vi.get_note_contents("A").unwrap();
+	vi.get_note_contents("B").unwrap();
+	vi.get_note_contents("C").unwrap();
+	let stats = vi.cache_stats();
+	assert_eq!((stats.notes, stats.bytes, stats.evictions), (2, 8, 1));
+	fs::write(p.join("C.md"), "new").unwrap();
+	vi.reindex_note("C").unwrap();
+	assert_eq!(vi.get_note_contents("C").unwrap(), "new");
+
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	vi.get_note_contents("A").unwrap();
+	assert_eq!(vi.cache_stats(), CacheStats::default());
+}
+
+#[test]
+fn vault_index_can_get_notes_of_type() {
+	let p = fixture_vault("notes_of_type");
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.note_extensions(&["md", "markdown"])
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let names: Vec<&str> = vi.notes_of_type("markdown").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(names, vec!["Three", "Two"]);
+	let names: Vec<&str> = vi.notes_of_type(".md").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(names, vec!["One"]);
+	assert!(vi.notes_of_type("txt").is_empty());
+}
+
+#[test]
+fn vault_index_explains_tab_indented_frontmatter() {
+	let p = fixture_vault("tab_frontmatter");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Lenient mode should index the note!");
+	assert_eq!(vi.warnings.len(), 1);
+	assert_eq!(vi.warnings[0].kind, WarningKind::TabIndentedFrontmatter);
+	assert!(vi.warnings[0].message.contains("Tabs"));
+	assert!(vi.warnings[0].message.contains("Line 3 of the front matter is indented with a tab (at column 1)"));
+	assert_eq!(vi.get_note("Fine").unwrap().properties["title"].as_display_string(), "A\ttab inside a value");
+
+	let err = vi.get_note("Tabs").unwrap().read().expect_err("Reading should fail on the tab!");
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	assert!(err.to_string().contains("indented with a tab"));
+
+	let err = VaultIndexBuilder::new(p.to_str()).strict(true).build().expect_err("Strict mode should reject the tab!");
+	assert!(err.to_string().contains("indented with a tab"));
+}
+
+#[test]
+fn vault_index_exports_documents_for_search() {
+	let p = fixture_vault("export_documents");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let docs: Vec<SearchDoc> = vi.export_documents();
+	assert_eq!(docs.len(), 2);
+	let bread = docs.iter().find(|doc| doc.name == "Bread").expect("Couldn't find the note's document!");
+	assert_eq!(bread.path, p.join("Recipes").join("Bread.md"));
+	assert_eq!(bread.tags, vec!["baking".to_string(), "kitchen".to_string()]);
+	assert!(bread.body_plaintext.contains("Sourdough"));
+	assert!(bread.body_plaintext.contains("Mix the starter with flour."));
+	assert!(!bread.body_plaintext.contains("tags:"));
+	let empty = docs.iter().find(|doc| doc.name == "Empty").expect("Couldn't find the empty note's document!");
+	assert!(empty.tags.is_empty());
+	assert_eq!(empty.body_plaintext, "");
+}
+
+#[test]
+fn vault_index_can_be_made_from_paths() {
+	let p = fixture_vault("from_paths");
+	let paths = vec![p.join("Folder").join("Deeper").join("Picture.png"), PathBuf::from("Folder/Changed.md")];
+	let vi = VaultIndex::from_paths(&p, &paths).expect("Couldn't make Vault Index from paths!");
+	assert_eq!(vi.notes.len(), 1);
+	let note = vi.get_note("Changed").expect("Couldn't find the indexed note!");
+	assert_eq!(note.local_path, Path::new("Folder").join("Changed"));
+	assert_eq!(note.links, vec!["Top".to_string()]);
+	assert_eq!(vi.tags.get("edited"), Some(&vec!["Changed".to_string()]));
+	assert!(vi.get_note("Untouched").is_none());
+	assert!(vi.get_note("Top").is_none());
+	assert!(vi.files.contains_key("Picture.png"));
+	let folders: Vec<PathBuf> = vi.folders.iter().map(|f| f.local_path.clone()).collect();
+	assert_eq!(folders, vec![PathBuf::from("Folder"), Path::new("Folder").join("Deeper")]);
+	let tree: Vec<&str> = vi.tree.as_flat_vec(0).iter().map(|node| node.name.as_str()).collect();
+	assert_eq!(tree, vec!["from_paths", "Folder", "Changed", "Deeper", "Picture.png"]);
+
+	let outside = env::temp_dir().join("Elsewhere.md");
+	let err = VaultIndex::from_paths(&p, &[outside]).expect_err("A path outside the vault should be rejected!");
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+	let err = VaultIndex::from_paths(&p, &[PathBuf::from("Missing.md")]).expect_err("A missing file should be an error!");
+	assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn vault_index_can_get_tags_in_folder() {
+	let p = fixture_vault("tags_in_folder");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let names: Vec<&str> = vi.notes_in_folder("Projects").iter().map(|n| n.name.as_str()).collect();
+	assert_eq!(names.len(), 2);
+	assert!(names.contains(&"Plan") && names.contains(&"Retro"));
+	assert_eq!(vi.tags_in_folder("Projects/"), vec!["active", "work", "work/planning", "work/review"]);
+	assert_eq!(vi.tags_in_folder("Projects/Old"), vec!["active", "work", "work/review"]);
+	assert_ne!(vi.tags_in_folder("Projects"), vi.all_tags());
+	assert_eq!(vi.tags_in_folder(""), vi.all_tags());
+	assert!(vi.tags_in_folder("Nowhere").is_empty());
+}
+
+#[test]
+fn note_can_get_callouts() {
+	let p = fixture_vault("callouts");
+	let vi = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	let callouts: Vec<Callout> = vi.get_note("Callouts").unwrap().callouts().expect("Couldn't read callouts!");
+	assert_eq!(callouts, vec![
+		Callout {
+			kind: "warning".to_string(),
+			title: Some("Mind the gap".to_string()),
+			body: "The platform is **narrow**.\n\nStand back.".to_string(),
+			line: 5,
+		},
+		Callout {
+			kind: "tip".to_string(),
+			title: None,
+			body: "Folded by default.".to_string(),
+			line: 12,
+		},
+	]);
+}
+
+#[test]
+fn vault_index_can_resolve_links_eagerly() {
+	let p = make_temp_vault("resolve_links_eagerly", &[
+		("Hub.md", "See [[Target]], [[Folder/Deep.md|the deep one]] and [[Missing]]."),
+		("Target.md", "Back to [[Hub#Top]]"),
+		("Folder/Deep.md", "No links"),
+	]);
+	let vi = VaultIndexBuilder::new(p.to_str())
+		.resolve_links_eagerly(true)
+		.build()
+		.expect("Couldn't make Vault Index!");
+	let hub = vi.get_note("Hub").unwrap();
+	assert_eq!(hub.links.len(), 3);
+	assert_eq!(hub.resolved_links, vec![Some("Target".to_string()), Some("Deep".to_string()), None]);
+	assert_eq!(vi.get_note("Target").unwrap().resolved_links, vec![Some("Hub".to_string())]);
+	assert!(vi.get_note("Deep").unwrap().resolved_links.is_empty());
+
+	let lazy = VaultIndex::new(p.to_str(), false).expect("Couldn't make Vault Index!");
+	assert!(lazy.get_note("Hub").unwrap().resolved_links.is_empty());
+
+	let mut vi = vi;
+	fs::write(p.join("Folder").join("Deep.md"), "Now links to [[Target]]").unwrap();
+	vi.reindex_note("Deep").expect("Couldn't reindex note!");
+	assert_eq!(vi.get_note("Deep").unwrap().resolved_links, vec![Some("Target".to_string())]);
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "zip")]
+use vault_dweller::VaultIndex;
+use std::env;
+use std::fs::{ self, File };
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+use zip::write::{ SimpleFileOptions, ZipWriter };
+
+fn get_vault_path() -> PathBuf {
+	let mut p = env::current_dir().unwrap();
+	p.push("tests");
+	p.push("TestVault");
+	p
+}
+
+fn zip_dir(zip: &mut ZipWriter<File>, dir: &Path, vault_path: &Path) {
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		let local_path: Vec<String> = path.strip_prefix(vault_path).unwrap().iter().map(|c| c.to_string_lossy().to_string()).collect();
+		if path.is_dir() {
+			zip.add_directory(local_path.join("/"), SimpleFileOptions::default()).unwrap();
+			zip_dir(zip, &path, vault_path);
+		} else {
+			zip.start_file(local_path.join("/"), SimpleFileOptions::default()).unwrap();
+			zip.write_all(&fs::read(&path).unwrap()).unwrap();
+		}
+	}
+}
+
+#[test]
+fn vault_index_can_read_zipped_vault() {
+	let vault_path = get_vault_path();
+	let zip_path = env::temp_dir().join("vault_dweller_zip_test.zip");
+	let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+	zip_dir(&mut zip, &vault_path, &vault_path);
+	zip.finish().unwrap();
+
+	let vi = VaultIndex::from_zip(&zip_path).expect("Couldn't make Vault Index!");
+	let on_disk = VaultIndex::new(vault_path.to_str(), true).expect("Couldn't make Vault Index!");
+	assert_eq!(vi.name, "vault_dweller_zip_test");
+	assert_eq!(vi.notes.len(), on_disk.notes.len());
+	assert_eq!(vi.files.len(), on_disk.files.len());
+	assert_eq!(vi.folders.len(), on_disk.folders.len());
+
+	let note = vi.get_note("Lorem Ipsum").expect("Couldn't get note!");
+	assert_eq!(note.tags, on_disk.get_note("Lorem Ipsum").unwrap().tags);
+	assert_eq!(
+		note.get_contents().expect("Couldn't read note from archive!"),
+		fs::read_to_string(vault_path.join("Folder A").join("Lorem Ipsum.md")).unwrap()
+	);
+	assert_eq!(note.lines().unwrap().count(), note.get_contents().unwrap().lines().count());
+	assert_eq!(
+		vi.graph_settings().expect("Couldn't read graph settings from archive!").link_distance,
+		on_disk.graph_settings().unwrap().link_distance
+	);
+}